@@ -0,0 +1,197 @@
+use crate::rules::{
+    NoDuplicate, NumberPool, NumberPoolByIndex, NumberRange, OddEven, OddEvenByIndex, OddEvenKey, PoolType, RuleTrait, Sequential,
+};
+
+//Finds rules that can never match with the other rules and count, so generating can stop
+//before it starts.  Only reports combinations that can never match.
+pub(crate) fn infeasible_reasons(expected_rules: &[Box<dyn RuleTrait>], count: usize) -> Vec<String> {
+    let mut reasons: Vec<String> = Vec::new();
+    if count == 0 {
+        return reasons;
+    }
+    let no_duplicate = expected_rules.iter().any(|x| x.as_any().is::<NoDuplicate>());
+    let number_range = expected_rules.iter().find_map(|x| x.as_any().downcast_ref::<NumberRange>());
+    //Range of every index, None when one of them has no range
+    let ranges: Option<Vec<(usize, usize)>> = number_range.and_then(|x| (0..count).map(|idx| x.range(idx)).collect());
+    let union: Option<Vec<(usize, usize)>> = ranges.as_ref().map(|x| union_of_ranges(x));
+    let number_range_str = number_range.map(|x| x.to_string()).unwrap_or_default();
+
+    if let Some(union) = &union {
+        let len: usize = union.iter().map(|(min, max)| (max - min).saturating_add(1)).sum();
+        if no_duplicate && len < count {
+            reasons.push(format!(
+                "NoDuplicate needs {} different numbers but NumberRange {} only has {}",
+                count, number_range_str, len
+            ));
+        }
+    }
+
+    for expected_rule in expected_rules {
+        if let Some(odd_even) = expected_rule.as_any().downcast_ref::<OddEven>() {
+            if odd_even.odd() + odd_even.even() < count {
+                reasons.push(format!(
+                    "OddEven needs {} odd and {} even numbers which is less than count: {}",
+                    odd_even.odd(), odd_even.even(), count
+                ));
+            }
+            if let (Some(ranges), Some(union)) = (&ranges, &union) {
+                for (key, needs) in [(OddEvenKey::Odd, odd_even.odd()), (OddEvenKey::Even, odd_even.even())] {
+                    let available = if no_duplicate {
+                        union.iter().map(|(min, max)| count_key(*min, *max, key)).sum::<usize>()
+                    } else {
+                        ranges.iter().filter(|(min, max)| count_key(*min, *max, key) > 0).count()
+                    };
+                    if available < needs {
+                        reasons.push(format!(
+                            "OddEven needs {} {:?} numbers but NumberRange {} only has {}",
+                            needs, key, number_range_str, available
+                        ));
+                    }
+                }
+            }
+        }
+
+        if let Some(number_pool) = expected_rule.as_any().downcast_ref::<NumberPool>() {
+            let mut keys: Vec<&String> = number_pool.number_pool_items().keys().collect();
+            keys.sort_unstable();
+            for key in keys {
+                let number_pool_item = &number_pool.number_pool_items()[key];
+                let available = match &union {
+                    Some(union) => pool_len_in_ranges(number_pool_item.pool(), union),
+                    None => number_pool_item.pool().len(),
+                };
+                if available < number_pool_item.needs() {
+                    reasons.push(format!(
+                        "NumberPool {} needs {} different numbers but only {} of them are in NumberRange {}",
+                        key, number_pool_item.needs(), available, number_range_str
+                    ));
+                }
+            }
+        }
+
+        if let Some(odd_even_by_index) = expected_rule.as_any().downcast_ref::<OddEvenByIndex>() {
+            if let Some(ranges) = &ranges {
+                let mut indexes: Vec<&usize> = odd_even_by_index.odd_even().keys().filter(|x| **x < count).collect();
+                indexes.sort_unstable();
+                for idx in indexes {
+                    let key = odd_even_by_index.odd_even()[idx];
+                    let (min, max) = ranges[*idx];
+                    if count_key(min, max, key) == 0 {
+                        reasons.push(format!(
+                            "OddEvenByIndex needs a {:?} number at index {} but NumberRange has no {:?} numbers between {} and {}",
+                            key, idx, key, min, max
+                        ));
+                    }
+                }
+            }
+        }
+
+        if let Some(number_pool_by_index) = expected_rule.as_any().downcast_ref::<NumberPoolByIndex>() {
+            for number_pool_item in number_pool_by_index.number_pool_items() {
+                let mut indexes: Vec<&usize> = number_pool_item.indexes().iter().filter(|x| **x < count).collect();
+                indexes.sort_unstable();
+                for idx in indexes {
+                    let available = match &ranges {
+                        Some(ranges) => pool_len_in_ranges(number_pool_item.pool(), &[ranges[*idx]]),
+                        None => number_pool_item.pool().len(),
+                    };
+                    if available == 0 {
+                        reasons.push(format!(
+                            "NumberPoolByIndex {} has no numbers for index {} in NumberRange {}",
+                            number_pool_item.key(), idx, number_range_str
+                        ));
+                    }
+                }
+            }
+        }
+
+        if let Some(sequential) = expected_rule.as_any().downcast_ref::<Sequential>() {
+            sequential_reasons(sequential, count, no_duplicate, union.as_deref(), &number_range_str, &mut reasons);
+        }
+    }
+    return reasons;
+}
+
+fn sequential_reasons(
+    sequential: &Sequential,
+    count: usize,
+    no_duplicate: bool,
+    union: Option<&[(usize, usize)]>,
+    number_range_str: &str,
+    reasons: &mut Vec<String>,
+) {
+    let seq_counts = sequential.seq_counts();
+    if seq_counts.windows(2).any(|x| x[0] > x[1]) {
+        reasons.push(format!("Sequential seq_counts {:?} have to be in ascending order to match", seq_counts));
+    }
+    if seq_counts.iter().any(|x| *x < 2) {
+        reasons.push(format!("Sequential seq_counts {:?} have to be 2 or more, a single number is not sequential", seq_counts));
+    }
+    let seq_total = sequential.not() + seq_counts.iter().sum::<usize>();
+    if seq_total < count {
+        reasons.push(format!("Sequential needs {} numbers which is less than count: {}", seq_total, count));
+    }
+    let union = match union {
+        Some(v) if !v.is_empty() => v,
+        _ => return,
+    };
+    let longest_seq = seq_counts.iter().copied().max().unwrap_or(0);
+    let longest_range = union.iter().map(|(min, max)| (max - min).saturating_add(1)).max().unwrap_or(0);
+    if longest_seq > longest_range {
+        reasons.push(format!(
+            "Sequential needs {} numbers in a row but NumberRange {} has at most {} numbers in a row",
+            longest_seq, number_range_str, longest_range
+        ));
+    }
+    //Without duplicates, every group of numbers needs a missing number before the next group
+    if no_duplicate && seq_total == count {
+        let groups = sequential.not() + seq_counts.len();
+        let needs = (count + groups).saturating_sub(1);
+        let span = (union[union.len() - 1].1 - union[0].0).saturating_add(1);
+        if needs > span {
+            reasons.push(format!(
+                "Sequential needs {} numbers from lowest to highest to keep {} groups apart but NumberRange {} only has {}",
+                needs, groups, number_range_str, span
+            ));
+        }
+    }
+}
+
+//Sorted ranges that do not overlap
+fn union_of_ranges(ranges: &[(usize, usize)]) -> Vec<(usize, usize)> {
+    let mut sorted: Vec<(usize, usize)> = ranges.iter().copied().filter(|(min, max)| min <= max).collect();
+    sorted.sort_unstable();
+    let mut union: Vec<(usize, usize)> = Vec::with_capacity(sorted.len());
+    for (min, max) in sorted {
+        match union.last_mut() {
+            Some(last) if min <= last.1.saturating_add(1) => last.1 = last.1.max(max),
+            _ => union.push((min, max)),
+        }
+    }
+    return union;
+}
+
+pub(crate) fn count_key(min: usize, max: usize, key: OddEvenKey) -> usize {
+    if min > max {
+        return 0;
+    }
+    let odd = (max - min) / 2 + usize::from(OddEven::is_odd(min) || OddEven::is_odd(max));
+    return match key {
+        OddEvenKey::Odd => odd,
+        OddEvenKey::Even => (max - min).saturating_add(1) - odd,
+    };
+}
+
+fn pool_len_in_ranges(pool: &PoolType, ranges: &[(usize, usize)]) -> usize {
+    return match pool {
+        PoolType::Set(set) => set.iter().filter(|x| ranges.iter().any(|(min, max)| *x >= min && *x <= max)).count(),
+        PoolType::MinMax(pool_min, pool_max) => ranges
+            .iter()
+            .map(|(min, max)| {
+                let low = (*min).max(*pool_min);
+                let high = (*max).min(*pool_max);
+                if low > high { 0 } else { high - low + 1 }
+            })
+            .sum(),
+    };
+}
@@ -0,0 +1,194 @@
+use crate::random::{random_numbers_with_rules, RandomResult, RandomResultType};
+use crate::random_error::RandomError;
+use crate::random_trait::{get_random_trait, RandomTrait};
+use crate::rules::RuleTrait;
+use crate::settings::Settings;
+use std::collections::HashSet;
+
+pub struct BatchResult {
+    results: Vec<RandomResult>,
+    missing: usize,
+    duplicates: usize,
+    error: Option<RandomError>,
+}
+
+impl BatchResult {
+    pub fn results(&self) -> &Vec<RandomResult> {
+        return &self.results;
+    }
+
+    pub fn into_results(self) -> Vec<RandomResult> {
+        return self.results;
+    }
+
+    pub fn numbers(&self) -> Vec<&Vec<usize>> {
+        return self.results.iter().map(|x| x.numbers().unwrap()).collect();
+    }
+
+    //How many of the requested results could not be created
+    pub fn missing(&self) -> usize {
+        return self.missing;
+    }
+
+    //How many created results were thrown away because they were the same as an earlier result
+    pub fn duplicates(&self) -> usize {
+        return self.duplicates;
+    }
+
+    //Error of the last result that could not be created
+    pub fn error(&self) -> Option<&RandomError> {
+        return self.error.as_ref();
+    }
+}
+
+pub fn random_numbers_batch(settings: &Settings, n: usize) -> BatchResult {
+    return random_numbers_batch_with_rng(settings, n, false, get_random_trait().as_ref());
+}
+
+//When order_insensitive is true, results with the same numbers in a different order count as the same result
+pub fn random_numbers_batch_with_rng(
+    settings: &Settings,
+    n: usize,
+    order_insensitive: bool,
+    rng: &dyn RandomTrait
+) -> BatchResult {
+    let mut seen: HashSet<Vec<usize>> = HashSet::with_capacity(n);
+    return fill_batch(settings, n, order_insensitive, rng, &mut |key| seen.insert(key));
+}
+
+fn bad_request_batch(settings: &Settings, n: usize) -> Option<BatchResult> {
+    if settings.bad_request_errors().is_empty() {
+        return None;
+    }
+    return Some(BatchResult {
+        results: Vec::new(),
+        missing: n,
+        duplicates: 0,
+        error: Some(RandomError::BadRequest { errors: settings.bad_request_errors().clone() }),
+    });
+}
+
+//Another try can not change these results
+fn is_retryable(status: RandomResultType) -> bool {
+    return !matches!(
+        status,
+        RandomResultType::Infeasible | RandomResultType::BadRequest | RandomResultType::TimedOut | RandomResultType::Cancelled
+    );
+}
+
+fn batch_key(random_result: &RandomResult, order_insensitive: bool) -> Vec<usize> {
+    let mut key = random_result.numbers().unwrap().clone();
+    if order_insensitive {
+        key.sort_unstable();
+    }
+    return key;
+}
+
+//is_new is given the key of every created result and returns false when it was already seen
+fn fill_batch(
+    settings: &Settings,
+    n: usize,
+    order_insensitive: bool,
+    rng: &dyn RandomTrait,
+    is_new: &mut dyn FnMut(Vec<usize>) -> bool
+) -> BatchResult {
+    if let Some(batch_result) = bad_request_batch(settings, n) {
+        return batch_result;
+    }
+    let mut batch_result = BatchResult { results: Vec::with_capacity(n), missing: 0, duplicates: 0, error: None };
+    let mut expected_rules: Vec<&dyn RuleTrait> = Vec::with_capacity(settings.expected_rules().len());
+    for idx in 0..n {
+        let mut is_added = false;
+        for _ in 0..=settings.max_duplicate_retries() {
+            let random_result = random_numbers_with_rules(settings, &mut expected_rules, rng);
+            if random_result.status() != RandomResultType::Success {
+                batch_result.error = random_result.numbers().err();
+                if !is_retryable(random_result.status()) {
+                    //Every following result would stop the same way
+                    batch_result.missing += n - idx;
+                    return batch_result;
+                }
+                break;
+            }
+            if is_new(batch_key(&random_result, order_insensitive)) {
+                batch_result.results.push(random_result);
+                is_added = true;
+                break;
+            }
+            batch_result.duplicates += 1;
+        }
+        if !is_added {
+            batch_result.missing += 1;
+        }
+    }
+    return batch_result;
+}
+
+#[cfg(feature="parallel")]
+pub fn random_numbers_batch_parallel(settings: &Settings, n: usize) -> BatchResult {
+    let threads = std::thread::available_parallelism().map(|x| x.get()).unwrap_or(1);
+    return random_numbers_batch_parallel_with_rng(settings, n, false, threads, get_random_trait().as_ref());
+}
+
+//Splits the batch over threads.  Every thread gets its own SeededRandom seeded from rng, so the
+//threads never share generator state and the same seeded rng always hands out the same streams.
+//Every thread only drops its own duplicates, the results are joined in thread order and the ones
+//another thread already created are created again on one more stream, so a seeded rng always
+//gives the same batch.
+#[cfg(feature="parallel")]
+pub fn random_numbers_batch_parallel_with_rng(
+    settings: &Settings,
+    n: usize,
+    order_insensitive: bool,
+    threads: usize,
+    rng: &dyn RandomTrait
+) -> BatchResult {
+    use crate::seeded_random::SeededRandom;
+
+    if let Some(batch_result) = bad_request_batch(settings, n) {
+        return batch_result;
+    }
+    let threads = threads.clamp(1, n.max(1));
+    let seeds: Vec<u64> = (0..threads).map(|_| rng.next_u64()).collect();
+    let top_up_seed = rng.next_u64();
+    let thread_results: Vec<BatchResult> = std::thread::scope(|scope| {
+        let handles: Vec<_> = seeds.iter().enumerate().map(|(idx, seed)| {
+            let thread_n = n / threads + usize::from(idx < n % threads);
+            scope.spawn(move || {
+                let thread_rng = SeededRandom::new(*seed);
+                let mut seen: HashSet<Vec<usize>> = HashSet::with_capacity(thread_n);
+                return fill_batch(settings, thread_n, order_insensitive, &thread_rng, &mut |key| seen.insert(key));
+            })
+        }).collect();
+        return handles.into_iter().map(|x| x.join().unwrap()).collect();
+    });
+
+    let mut seen: HashSet<Vec<usize>> = HashSet::with_capacity(n);
+    let mut batch_result = BatchResult { results: Vec::with_capacity(n), missing: 0, duplicates: 0, error: None };
+    let mut top_up: usize = 0;
+    for thread_result in thread_results {
+        for random_result in thread_result.results {
+            if seen.insert(batch_key(&random_result, order_insensitive)) {
+                batch_result.results.push(random_result);
+            } else {
+                batch_result.duplicates += 1;
+                top_up += 1;
+            }
+        }
+        batch_result.missing += thread_result.missing;
+        batch_result.duplicates += thread_result.duplicates;
+        if thread_result.error.is_some() {
+            batch_result.error = thread_result.error;
+        }
+    }
+    if top_up > 0 {
+        let top_up_result = fill_batch(settings, top_up, order_insensitive, &SeededRandom::new(top_up_seed), &mut |key| seen.insert(key));
+        batch_result.results.extend(top_up_result.results);
+        batch_result.missing += top_up_result.missing;
+        batch_result.duplicates += top_up_result.duplicates;
+        if top_up_result.error.is_some() {
+            batch_result.error = top_up_result.error;
+        }
+    }
+    return batch_result;
+}
@@ -0,0 +1,79 @@
+use crate::random::RandomResultType;
+use crate::random_error::RandomError;
+use crate::settings::Settings;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+//Stops creating numbers from another thread.  Every clone cancels the same numbers, so keep one
+//and give a clone to SettingsBuilder::cancellation_token.
+#[derive(Debug, Default, Clone)]
+pub struct CancellationToken {
+    is_cancelled: Arc<AtomicBool>,
+}
+
+impl CancellationToken {
+    pub fn new() -> CancellationToken {
+        return CancellationToken::default();
+    }
+
+    pub fn cancel(&self) {
+        self.is_cancelled.store(true, Ordering::Relaxed);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        return self.is_cancelled.load(Ordering::Relaxed);
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, Copy, Clone)]
+pub(crate) enum StopReason {
+    TimedOut,
+    Cancelled,
+}
+
+impl StopReason {
+    pub(crate) fn status(&self) -> RandomResultType {
+        return match self {
+            StopReason::TimedOut => RandomResultType::TimedOut,
+            StopReason::Cancelled => RandomResultType::Cancelled,
+        };
+    }
+
+    pub(crate) fn error(&self, attempts: usize, settings: &Settings) -> RandomError {
+        return match self {
+            StopReason::TimedOut => RandomError::TimedOut { attempts, timeout: settings.timeout().unwrap_or_default() },
+            StopReason::Cancelled => RandomError::Cancelled { attempts },
+        };
+    }
+}
+
+//The timeout and cancellation token of settings from the time numbers started to be created
+pub(crate) struct Deadline<'a> {
+    start: Option<Instant>,
+    timeout: Option<Duration>,
+    cancellation_token: Option<&'a CancellationToken>,
+}
+
+impl<'a> Deadline<'a> {
+    pub(crate) fn new(settings: &'a Settings) -> Deadline<'a> {
+        return Deadline {
+            //Only reads the clock when there is a timeout
+            start: settings.timeout().map(|_| Instant::now()),
+            timeout: settings.timeout(),
+            cancellation_token: settings.cancellation_token().as_ref(),
+        };
+    }
+
+    pub(crate) fn stop_reason(&self) -> Option<StopReason> {
+        if self.cancellation_token.is_some_and(|x| x.is_cancelled()) {
+            return Some(StopReason::Cancelled);
+        }
+        if let (Some(start), Some(timeout)) = (self.start, self.timeout) {
+            if start.elapsed() >= timeout {
+                return Some(StopReason::TimedOut);
+            }
+        }
+        return None;
+    }
+}
@@ -18,6 +18,10 @@ impl DefaultRandom {
 
 #[cfg(feature="rand")]
 impl RandomTrait for DefaultRandom {
+    fn next_u64(&self) -> u64 {
+        return thread_rng().gen();
+    }
+
     fn get_number(&self, min: usize, max: usize) -> usize {
         return thread_rng().gen_range(min..=max); 
     }
@@ -0,0 +1,116 @@
+use crate::random_error::RandomError;
+use crate::random_trait::RandomTrait;
+use crate::settings::{Settings, SolverType};
+use crate::solutions::{index_ranges, SolutionCount};
+use std::fmt;
+use std::fmt::{Display, Formatter};
+
+//Bits of the state of a seeded generator, every number it gives comes from it
+const SEED_BITS: f64 = 64.0;
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct EntropyReport {
+    bits: f64,
+    rule_bits: f64,
+    range_bits: f64,
+    solution_count: SolutionCount,
+    is_uniform: bool,
+    is_cryptographically_secure: bool,
+    min_entropy_bits: Option<f64>,
+    warnings: Vec<String>,
+}
+
+impl EntropyReport {
+    //Bits of entropy of the numbers, the smallest of rule_bits and the bits of a seeded rng
+    pub fn bits(&self) -> f64 {
+        return self.bits;
+    }
+
+    //Bits of entropy when one of the matches of the rules is picked uniformly
+    pub fn rule_bits(&self) -> f64 {
+        return self.rule_bits;
+    }
+
+    //Bits of entropy of the NumberRange, or of the pools without one, without any other rule
+    pub fn range_bits(&self) -> f64 {
+        return self.range_bits;
+    }
+
+    pub fn solution_count(&self) -> &SolutionCount {
+        return &self.solution_count;
+    }
+
+    //False when the solver does not pick every match with the same chance, bits is then the most
+    //entropy the numbers can have
+    pub fn is_uniform(&self) -> bool {
+        return self.is_uniform;
+    }
+
+    pub fn is_cryptographically_secure(&self) -> bool {
+        return self.is_cryptographically_secure;
+    }
+
+    pub fn is_below_min(&self) -> bool {
+        return self.min_entropy_bits.is_some_and(|x| self.bits < x);
+    }
+
+    pub fn warnings(&self) -> &Vec<String> {
+        return &self.warnings;
+    }
+}
+
+impl Display for EntropyReport {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        write!(f, "{:.2} bits of entropy ({} matches)", self.bits, self.solution_count)?;
+        for warning in &self.warnings {
+            write!(f, "\nWarning : {}", warning)?;
+        }
+        return Ok(());
+    }
+}
+
+//Entropy of the numbers settings creates with rng, solution_count is the count of settings
+pub(crate) fn entropy_report(
+    settings: &Settings,
+    solution_count: SolutionCount,
+    rng: &dyn RandomTrait
+) -> Result<EntropyReport, RandomError> {
+    let range_bits: f64 = index_ranges(settings)?.iter().map(|(min, max)| ((max - min) as f64 + 1.0).log2()).sum();
+    let rule_bits = if solution_count.count() >= 1.0 { solution_count.log2() } else { 0.0 };
+    let is_uniform = settings.solver() == SolverType::Uniform;
+    let is_cryptographically_secure = rng.is_cryptographically_secure();
+    let mut bits = rule_bits;
+    let mut warnings: Vec<String> = Vec::new();
+    if !is_uniform {
+        warnings.push(format!(
+            "{:?} solver does not pick every match with the same chance, the numbers have at most {:.2} bits",
+            settings.solver(), rule_bits
+        ));
+    }
+    if !is_cryptographically_secure {
+        let mut warning = String::from("rng is not cryptographically secure");
+        if rng.seed().is_some() {
+            bits = bits.min(SEED_BITS);
+            warning.push_str(&format!(", every number comes from its {} bit seed", SEED_BITS));
+        }
+        warnings.push(warning);
+    }
+    if let Some(min_entropy_bits) = settings.min_entropy_bits() {
+        if bits < min_entropy_bits {
+            warnings.push(format!(
+                "Entropy of {:.2} bits is below min_entropy_bits: {:.2}, the rules leave {:.2} of the {:.2} bits of the ranges",
+                bits, min_entropy_bits, rule_bits, range_bits
+            ));
+        }
+    }
+    return Ok(EntropyReport {
+        bits,
+        rule_bits,
+        range_bits,
+        solution_count,
+        is_uniform,
+        is_cryptographically_secure,
+        min_entropy_bits: settings.min_entropy_bits(),
+        warnings,
+    });
+}
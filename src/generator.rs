@@ -0,0 +1,35 @@
+use crate::random::random_numbers_with_rules;
+use crate::random_error::RandomError;
+use crate::random_trait::{get_random_trait, RandomTrait};
+use crate::rules::RuleTrait;
+use crate::settings::Settings;
+
+//Never ends, every call to next creates new numbers.  Use take or another adapter that stops
+//before collecting.
+pub struct Generator<'a> {
+    settings: &'a Settings,
+    rng: &'a dyn RandomTrait,
+    expected_rules: Vec<&'a dyn RuleTrait>,
+}
+
+impl<'a> Generator<'a> {
+    pub fn new(settings: &'a Settings) -> Generator<'a> {
+        return Generator::with_rng(settings, get_random_trait().as_ref());
+    }
+
+    pub fn with_rng(settings: &'a Settings, rng: &'a dyn RandomTrait) -> Generator<'a> {
+        return Generator { settings, rng, expected_rules: Vec::with_capacity(settings.expected_rules().len()) };
+    }
+}
+
+impl Iterator for Generator<'_> {
+    type Item = Result<Vec<usize>, RandomError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        return Some(random_numbers_with_rules(self.settings, &mut self.expected_rules, self.rng).into_numbers());
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        return (usize::MAX, None);
+    }
+}
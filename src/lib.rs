@@ -5,6 +5,21 @@ pub mod settings;
 pub mod rules;
 pub mod random_trait;
 pub mod default_random;
+pub mod seeded_random;
+pub mod replay;
+pub mod os_random;
+pub mod random_error;
+pub mod batch;
+pub mod generator;
+pub mod solutions;
+pub mod entropy;
+pub mod trace;
+pub mod rule_stats;
+pub mod cancellation;
+pub mod observer;
+mod solver;
+mod analyze;
+mod uniform;
 
 #[cfg(test)]
 mod tests;
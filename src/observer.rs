@@ -0,0 +1,34 @@
+use crate::random::RandomResult;
+use crate::rules::MaybeSendSync;
+
+//The check that turned numbers down
+#[derive(Debug, PartialEq, Eq, Hash, Copy, Clone)]
+pub enum RejectionKind {
+    //get_numbers of the rule returned an error
+    GetNumbers,
+    //The rule picked more numbers than count, the rule name is "Too Many Numbers Selected."
+    TooManyNumbers,
+    WithinRange,
+    Match,
+}
+
+//Called while numbers are created, give it to SettingsBuilder::observer.  Every method does
+//nothing by default, so only the wanted ones have to be written.  Methods take &self, use
+//atomics or a Mutex to count.  The incremental solver calls every method, the uniform solver
+//calls on_attempt, on_rule_rejection with RejectionKind::Match and on_finish, the backtracking
+//solver only calls on_finish.
+pub trait GenerationObserver: MaybeSendSync {
+    fn on_attempt(&self, _attempt: usize) {}
+
+    //numbers are the numbers of the rule, selected are all numbers with them
+    fn on_candidate(&self, _attempt: usize, _rule_name: &str, _numbers: &[usize], _selected: &[usize]) {}
+
+    //Exclude rules are named "exr-<exclude name>"
+    fn on_rule_rejection(&self, _attempt: usize, _rule_name: &str, _kind: RejectionKind, _reason: &str) {}
+
+    //Numbers were removed because the same error happened too many times
+    fn on_reset(&self, _attempt: usize, _rule_name: &str, _reason: &str) {}
+
+    //Called once with the result of every call that creates numbers
+    fn on_finish(&self, _random_result: &RandomResult) {}
+}
@@ -0,0 +1,30 @@
+#[cfg(feature="csprng")]
+use crate::random_trait::RandomTrait;
+
+
+//Draws from the operating system's entropy source, use this to create passwords and other credentials
+#[cfg(feature="csprng")]
+#[derive(Debug, Clone)]
+pub struct OsRandom {}
+
+#[cfg(feature="csprng")]
+impl OsRandom {
+    pub fn new() -> Box<dyn RandomTrait> {
+        return Box::new(OsRandom {  });
+    }
+}
+
+#[cfg(feature="csprng")]
+impl RandomTrait for OsRandom {
+    fn next_u64(&self) -> u64 {
+        let mut bytes = [0_u8; 8];
+        if let Err(e) = getrandom::getrandom(&mut bytes) {
+            panic!("Could not get random bytes from the operating system: {}", e);
+        }
+        return u64::from_le_bytes(bytes);
+    }
+
+    fn is_cryptographically_secure(&self) -> bool {
+        return true;
+    }
+}
@@ -1,11 +1,20 @@
 use once_cell::sync::OnceCell;
 
-use crate::random_trait::shuffle_vec;
+use crate::random_trait::{get_random_trait, shuffle_vec_with_rng, RandomTrait};
+use crate::cancellation::Deadline;
+use crate::entropy::EntropyReport;
+use crate::observer::RejectionKind;
+use crate::random_error::RandomError;
+use crate::replay::{RecordingRandom, ReplayToken};
 use crate::rules::{
-    IsWithinErrorType, MapAnyValue, RuleTrait, ExcludeRuleTrait,
+    IsWithinErrorType, MapAnyValue, RuleTrait, ExcludeRuleTrait, NumberRange,
 };
-use crate::settings::Settings;
-use std::collections::{HashMap, HashSet};
+use crate::settings::{LogLevel, Settings, SolverType};
+use crate::solver::{Solver, SolverOutcome};
+use crate::trace::{to_json_lines, TraceEntry, TraceEvent, TraceRecorder};
+use crate::rule_stats::{RuleStats, RuleStatsRecorder};
+use crate::uniform::{UniformOutcome, UniformSampler};
+use std::collections::{HashMap, HashSet, VecDeque};
 use std::fmt;
 use std::fmt::{Debug, Formatter, Result};
 use std::hash::Hash;
@@ -15,6 +24,15 @@ pub enum RandomResultType {
     Success,
     BadRequest,
     Failed,
+    //No numbers can match the rules, found by Settings::analyze, by the backtracking solver
+    //searching every combination or by checking the fixed numbers of complete_numbers
+    Infeasible,
+    //Stopped by the timeout of Settings, the result has the stats of the attempts so far
+    TimedOut,
+    //Stopped by the cancellation token of Settings, the result has the stats of the attempts so far
+    Cancelled,
+    //The backtracking solver reached max_solver_nodes before it found numbers or proved there are none
+    Undecided,
 }
 
 pub struct RandomResult {
@@ -22,7 +40,12 @@ pub struct RandomResult {
     numbers: Vec<usize>,
     attempts: usize,
     logs: Vec<Log>,
-    clear_err_tracker: Vec<usize>
+    clear_err_tracker: Vec<usize>,
+    replay_token: ReplayToken,
+    entropy: Option<EntropyReport>,
+    trace: Vec<TraceEntry>,
+    rule_stats: HashMap<String, RuleStats>,
+    error: Option<RandomError>,
 }
 
 impl Debug for RandomResult {
@@ -36,31 +59,50 @@ impl RandomResult {
         return self.status;
     }
 
-    pub fn numbers(&self) -> std::result::Result<&Vec<usize>, String> {
+    pub fn numbers(&self) -> std::result::Result<&Vec<usize>, RandomError> {
         match self.status {
             RandomResultType::Success => {
                 return Ok(&self.numbers);
             },
-            _ => Err("Error! Please Check Logs!".to_string())
+            _ => Err(self.error())
         }
     }
-    pub fn string(&self, shuffle: bool) -> std::result::Result<String, String> {
+    pub fn into_numbers(self) -> std::result::Result<Vec<usize>, RandomError> {
+        match self.status {
+            RandomResultType::Success => {
+                return Ok(self.numbers);
+            },
+            _ => Err(self.error())
+        }
+    }
+    pub fn string(&self, shuffle: bool) -> std::result::Result<String, RandomError> {
+        return self.string_with_rng(shuffle, get_random_trait().as_ref());
+    }
+    pub fn string_with_rng(&self, shuffle: bool, rng: &dyn RandomTrait) -> std::result::Result<String, RandomError> {
         match self.status {
             RandomResultType::Success => {
                 let mut random_string = String::new();
                 let mut numbers: Vec<usize> = self.numbers.clone();
                 if shuffle {
-                    shuffle_vec(&mut numbers);
-                    //numbers.shuffle(&mut thread_rng()); TODO remove
+                    shuffle_vec_with_rng(&mut numbers, rng);
                 }
-                for digit in numbers {
-                    random_string.push(char::from_u32(digit as u32).unwrap());
+                for (idx, digit) in numbers.into_iter().enumerate() {
+                    match u32::try_from(digit).ok().and_then(char::from_u32) {
+                        Some(c) => random_string.push(c),
+                        None => return Err(RandomError::InvalidCharCode { number: digit, idx }),
+                    }
                 }
                 return Ok(random_string);
             },
-            _ => Err("Error! Please Check Logs!".to_string())
+            _ => Err(self.error())
         }
     }
+    fn error(&self) -> RandomError {
+        return match &self.error {
+            Some(e) => e.clone(),
+            None => RandomError::BadRequest { errors: vec![format!("No numbers for status {:?}", self.status)] },
+        };
+    }
     pub fn attempts(&self) -> usize {
         return self.attempts;
     }
@@ -70,27 +112,52 @@ impl RandomResult {
     pub fn clear_err_tracker(&self) -> &Vec<usize> {
         return &self.clear_err_tracker;
     }
+    //Only has the seed of the generator unless SettingsBuilder::record_draws is set.  A generator
+    //without a seed, like the default one, only has the draws when numbers were not created, so a
+    //successful result of it can only be replayed with record_draws.
+    pub fn replay_token(&self) -> &ReplayToken {
+        return &self.replay_token;
+    }
+    //Entropy of the numbers, only when Settings has min_entropy_bits.  When the report could not be
+    //made the reason is in the logs.
+    pub fn entropy(&self) -> Option<&EntropyReport> {
+        return self.entropy.as_ref();
+    }
+    //Events of creating the numbers, only with LogLevel::Trace
+    pub fn trace(&self) -> &Vec<TraceEntry> {
+        return &self.trace;
+    }
+    pub fn trace_json_lines(&self) -> String {
+        return to_json_lines(&self.trace);
+    }
+    //What every rule did by rule name, shows which rule makes creating the numbers slow
+    pub fn rule_stats(&self) -> &HashMap<String, RuleStats> {
+        return &self.rule_stats;
+    }
+    //New numbers at indexes of a successful result, see reroll_numbers
+    pub fn reroll(&self, settings: &Settings, indexes: &[usize]) -> std::result::Result<RandomResult, RandomError> {
+        return Ok(reroll_numbers(settings, self.numbers()?, indexes));
+    }
 }
 
+//Only shows the logs the log level of Settings kept
 impl fmt::Display for RandomResult {
     fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
         write!(
             fmt,
-            "\n\nStatus - {:?}\nNumbers - {:?}\nAttempts - {}\nClear Errs - {:?}\n{}",
+            "\n\nStatus - {:?}\nNumbers - {:?}\nAttempts - {}\nClear Errs - {:?}",
             self.status(),
             self.numbers(),
             self.attempts,
             self.clear_err_tracker(),
-            self.logs
-                .iter()
-                .map(|x| if x.to_string().starts_with("Info  : Attempt - ") {
-                    format!("\n{}", x)
-                } else {
-                    x.to_string()
-                })
-                .collect::<Vec<String>>()
-                .join("\n")
-        )
+        )?;
+        for log in &self.logs {
+            if matches!(log, Log::Info { msg } if msg.starts_with("Attempt - ")) {
+                writeln!(fmt)?;
+            }
+            write!(fmt, "\n{}", log)?;
+        }
+        return Ok(());
     }
 }
 
@@ -104,20 +171,49 @@ fn set_numbers(
 }
 
 fn clear_numbers(
-    logs: &mut Vec<Log>,
+    logs: &mut VecDeque<Log>,
     numbers: &mut Vec<usize>,
-    _settings: &Settings,
+    settings: &Settings,
 ) {
     numbers.clear();
-    logs.push(Log::Info {
+    extend_with_fixed(numbers, &[], settings);
+    push_log(logs, settings, LogLevel::Info, || Log::Info {
         msg: format!("CLEAR - {:?}", numbers),
     });
 }
 
+//Appends new_numbers to numbers, the fixed numbers of settings are put at their index first and
+//the ones right after the last new number are appended too
+fn extend_with_fixed(numbers: &mut Vec<usize>, new_numbers: &[usize], settings: &Settings) {
+    for number in new_numbers {
+        while let Some(v) = settings.fixed_number(numbers.len()) {
+            numbers.push(v);
+        }
+        numbers.push(*number);
+    }
+    while let Some(v) = settings.fixed_number(numbers.len()) {
+        numbers.push(v);
+    }
+}
+
+//Only creates the log when the log level of settings keeps logs of level, when there are
+//max_logs logs the oldest one is dropped
+fn push_log(logs: &mut VecDeque<Log>, settings: &Settings, level: LogLevel, log: impl FnOnce() -> Log) {
+    if !settings.log_level().includes(level) || settings.max_logs() == 0 {
+        return;
+    }
+    if logs.len() >= settings.max_logs() {
+        logs.pop_front();
+    }
+    logs.push_back(log());
+}
+
 #[derive(Debug, Clone)]
+#[non_exhaustive]
 pub enum Log {
     Error { msg: String },
     Info { msg: String },
+    Trace { msg: String },
 }
 
 impl fmt::Display for Log {
@@ -125,11 +221,15 @@ impl fmt::Display for Log {
         match self {
             Log::Error { msg } => write!(fmt, "Error : {}", msg.to_owned())?,
             Log::Info { msg } => write!(fmt, "Info  : {}", msg.to_owned())?,
+            Log::Trace { msg } => write!(fmt, "Trace : {}", msg.to_owned())?,
         }
         Ok(())
     }
 }
 
+//True when the numbers were reset because of too many of the same error.  violating_positions
+//is only called on a reset, see remove_numbers.
+//Keeps the signature of earlier versions, the numbers are all removed on a reset
 #[allow(clippy::too_many_arguments)]
 pub fn halt_from_error(
     err_for_err_tracker: &str,
@@ -141,30 +241,174 @@ pub fn halt_from_error(
     settings: &Settings,
     clear_err_tracker: &mut Vec<usize>,
 ) {
-    logs.push(Log::Error {
+    let mut log_deque: VecDeque<Log> = VecDeque::from(std::mem::take(logs));
+    halt_from_error_with_positions(
+        err_for_err_tracker,
+        err,
+        &mut log_deque,
+        err_tracker,
+        &mut HashMap::new(),
+        attempts,
+        numbers,
+        settings,
+        clear_err_tracker,
+        &mut None,
+        || None,
+    );
+    *logs = Vec::from(log_deque);
+}
+
+#[allow(clippy::too_many_arguments)]
+fn halt_from_error_with_positions(
+    err_for_err_tracker: &str,
+    err: &str,
+    logs: &mut VecDeque<Log>,
+    err_tracker: &mut HashMap<String, usize>,
+    failure_tracker: &mut HashMap<String, usize>,
+    attempts: usize,
+    numbers: &mut Vec<usize>,
+    settings: &Settings,
+    clear_err_tracker: &mut Vec<usize>,
+    kept_numbers: &mut Option<Vec<usize>>,
+    violating_positions: impl FnOnce() -> Option<Vec<usize>>,
+) -> bool {
+    push_log(logs, settings, LogLevel::Info, || Log::Error {
         msg: err.to_owned(),
     });
+    if let Some(observer) = settings.observer() {
+        observer.on_rule_rejection(attempts, rule_name_of_key(err_for_err_tracker), rejection_kind_of_key(err_for_err_tracker), err);
+    }
+    #[cfg(feature="tracing")]
+    tracing::debug!(
+        attempt = attempts,
+        rule_name = rule_name_of_key(err_for_err_tracker),
+        kind = ?rejection_kind_of_key(err_for_err_tracker),
+        reason = err,
+        "rule rejected numbers"
+    );
     let count = *err_tracker
         .entry(err_for_err_tracker.to_owned())
         .and_modify(|x| *x += 1)
         .or_insert(1);
+    *failure_tracker.entry(err_for_err_tracker.to_owned()).or_insert(0) += 1;
     if count > (settings.max_specific_error_count()) {
-        logs.push(Log::Error {
+        push_log(logs, settings, LogLevel::Info, || Log::Error {
             msg: format!("Reset Because Of Too Many Same Errors - {}: {}", err_for_err_tracker, err),
         });
-        reset(logs, numbers, settings, err_tracker);
+        remove_numbers(logs, numbers, settings, kept_numbers, violating_positions());
+        err_tracker.clear();
         clear_err_tracker.push(attempts);
+        if let Some(observer) = settings.observer() {
+            observer.on_reset(attempts, rule_name_of_key(err_for_err_tracker), err);
+        }
+        #[cfg(feature="tracing")]
+        tracing::info!(attempt = attempts, rule_name = rule_name_of_key(err_for_err_tracker), reason = err, "numbers were reset");
+        return true;
     }
+    return false;
 }
 
-fn reset(
-    logs: &mut Vec<Log>,
+//Removes the numbers at violating_positions, which are positions of the numbers that were checked
+//so the ones past numbers are skipped.  When the order of the numbers matters the numbers after
+//the first position are removed too, so no number moves to another index.  All numbers are
+//removed when the rule has no positions.  Fixed numbers are never removed.  When only the numbers
+//past numbers are wrong, numbers are kept and remembered in kept_numbers.  The next time the same
+//numbers would be kept they are all removed, no new numbers fit them.
+fn remove_numbers(
+    logs: &mut VecDeque<Log>,
     numbers: &mut Vec<usize>,
     settings: &Settings,
-    err_tracker: &mut HashMap<String, usize>,
+    kept_numbers: &mut Option<Vec<usize>>,
+    violating_positions: Option<Vec<usize>>,
 ) {
-    clear_numbers(logs, numbers, settings);
-    err_tracker.clear();
+    let mut positions: Vec<usize> = match violating_positions {
+        Some(v) => v,
+        None => {
+            clear_numbers(logs, numbers, settings);
+            return;
+        },
+    };
+    positions.retain(|x| *x < numbers.len() && settings.fixed_number(*x).is_none());
+    if positions.is_empty() {
+        if kept_numbers.as_ref() != Some(numbers) {
+            //Only the numbers that were not added yet are wrong, they are already thrown away
+            *kept_numbers = Some(numbers.to_vec());
+            push_log(logs, settings, LogLevel::Info, || Log::Info {
+                msg: format!("REMOVE - Positions []; {:?}", numbers),
+            });
+            return;
+        }
+        //No new numbers fit them
+        *kept_numbers = None;
+        clear_numbers(logs, numbers, settings);
+        return;
+    }
+    *kept_numbers = None;
+    if settings.is_order_independent() && settings.fixed_numbers().is_empty() {
+        let mut idx = 0;
+        numbers.retain(|_| {
+            idx += 1;
+            return !positions.contains(&(idx - 1));
+        });
+    } else {
+        numbers.truncate(*positions.iter().min().unwrap());
+        extend_with_fixed(numbers, &[], settings);
+    }
+    push_log(logs, settings, LogLevel::Info, || Log::Info {
+        msg: format!("REMOVE - Positions {:?}; {:?}", positions, numbers),
+    });
+}
+
+//Checks the fixed numbers before any number is created.  Ok(true) when the fixed numbers are all
+//the numbers and match the rules.
+fn check_fixed_numbers(
+    expected_rules: &[&dyn RuleTrait],
+    settings: &Settings,
+    numbers: &Vec<usize>,
+    rng: &dyn RandomTrait,
+    logs: &mut VecDeque<Log>,
+    rule_stats: &mut RuleStatsRecorder,
+) -> std::result::Result<bool, RandomError> {
+    let number_range = settings.expected_rules().iter().find_map(|x| x.as_any().downcast_ref::<NumberRange>());
+    for (idx, fixed_number) in settings.fixed_numbers().iter().enumerate() {
+        if let (Some(v), Some((min, max))) = (fixed_number, number_range.and_then(|x| x.range(idx))) {
+            if *v < min || *v > max {
+                return Err(RandomError::FixedNumbersRejected {
+                    rule_name: String::from("NumberRange"),
+                    reason: format!("Fixed number {} at index {} is not within range of min: {} and max: {}", v, idx, min, max),
+                });
+            }
+        }
+    }
+    let shared_data: HashMap<String, HashMap<String, MapAnyValue>> = HashMap::new();
+    let current_data = CurrentData::with_rng(numbers, settings, &shared_data, rng);
+    if numbers.len() == settings.count() {
+        return match is_match_check(expected_rules, settings.exclude_rules(), settings, &current_data, logs, rule_stats) {
+            Ok(_) => Ok(true),
+            Err(e) => Err(RandomError::FixedNumbersRejected { rule_name: e.1, reason: e.0 }),
+        };
+    }
+    if let Err(e) = is_within_range_check(expected_rules, settings.exclude_rules(), settings, &current_data, logs, rule_stats) {
+        return Err(RandomError::FixedNumbersRejected { rule_name: e.3, reason: e.1 });
+    }
+    //Without an order the fixed numbers after the first missing one can be checked with the others
+    let all_fixed_numbers: Vec<usize> = settings.fixed_numbers().iter().flatten().copied().collect();
+    if settings.is_order_independent() && all_fixed_numbers.len() > numbers.len() {
+        let current_data = CurrentData::with_rng(&all_fixed_numbers, settings, &shared_data, rng);
+        if let Err(e) = is_within_range_check(expected_rules, settings.exclude_rules(), settings, &current_data, logs, rule_stats) {
+            return Err(RandomError::FixedNumbersRejected { rule_name: e.3, reason: e.1 });
+        }
+    }
+    return Ok(false);
+}
+
+//Positions of the numbers of current_data that made the rule named rule_name fail, exclude rules
+//can not tell
+fn violating_positions(expected_rules: &[&dyn RuleTrait], rule_name: &str, current_data: &CurrentData) -> Option<Vec<usize>> {
+    return expected_rules
+        .iter()
+        .find(|x| x.name() == rule_name)
+        .and_then(|x| x.violating_positions(current_data));
 }
 
 pub struct CurrentData<'a> {
@@ -173,12 +417,17 @@ pub struct CurrentData<'a> {
     selected_numbers_sorted: OnceCell<Vec<usize>>,
     settings: &'a Settings,
     shared_data: &'a HashMap<String, HashMap<String, MapAnyValue>>,
+    rng: Option<&'a dyn RandomTrait>,
 }
 
 impl<'a> CurrentData<'a> {
 
     pub fn new(selected_numbers: &'a Vec<usize>, settings: &'a Settings, shared_data: &'a HashMap<String, HashMap<String, MapAnyValue>>) -> CurrentData<'a> {
-        return CurrentData { selected_numbers, settings, shared_data, selected_numbers_set: OnceCell::new(), selected_numbers_sorted: OnceCell::new() };
+        return CurrentData { selected_numbers, settings, shared_data, selected_numbers_set: OnceCell::new(), selected_numbers_sorted: OnceCell::new(), rng: None };
+    }
+
+    pub fn with_rng(selected_numbers: &'a Vec<usize>, settings: &'a Settings, shared_data: &'a HashMap<String, HashMap<String, MapAnyValue>>, rng: &'a dyn RandomTrait) -> CurrentData<'a> {
+        return CurrentData { selected_numbers, settings, shared_data, selected_numbers_set: OnceCell::new(), selected_numbers_sorted: OnceCell::new(), rng: Some(rng) };
     }
 
     pub fn from_current_data(current_data: &'a CurrentData, shared_data: &'a HashMap<String, HashMap<String, MapAnyValue>>) -> CurrentData<'a> {
@@ -188,6 +437,7 @@ impl<'a> CurrentData<'a> {
             shared_data, 
             selected_numbers_set: OnceCell::new(), 
             selected_numbers_sorted: OnceCell::new(), 
+            rng: current_data.rng,
         };
         if current_data.selected_numbers_set.get().is_some() {
             let _ = new_current_data.selected_numbers_set.set(current_data.selected_numbers_set.get().unwrap().clone());
@@ -210,6 +460,13 @@ impl<'a> CurrentData<'a> {
         return self.shared_data;
     }
 
+    pub fn rng(&self) -> &'a dyn RandomTrait {
+        return match self.rng {
+            Some(rng) => rng,
+            None => get_random_trait().as_ref(),
+        };
+    }
+
     pub fn selected_numbers_set(&self) -> &HashSet<usize> {
         return self.selected_numbers_set.get_or_init(|| {
             self.selected_numbers.iter().copied().collect()
@@ -226,29 +483,215 @@ impl<'a> CurrentData<'a> {
 }
 
 pub fn random_numbers(settings: &Settings) -> RandomResult {
+    return random_numbers_with_rng(settings, get_random_trait().as_ref());
+}
+
+pub fn random_numbers_with_rng(settings: &Settings, rng: &dyn RandomTrait) -> RandomResult {
+    return random_numbers_with_rules(settings, &mut Vec::new(), rng);
+}
+
+//expected_rules is only working space for shuffling the rules of settings, reusing it lets many
+//results be created without allocating it again
+pub(crate) fn random_numbers_with_rules<'a>(
+    settings: &'a Settings,
+    expected_rules: &mut Vec<&'a dyn RuleTrait>,
+    rng: &dyn RandomTrait
+) -> RandomResult {
+    #[cfg(feature="tracing")]
+    let _span = tracing::info_span!("random_numbers", count = settings.count(), solver = ?settings.solver()).entered();
+    //A replay token of a secure generator would give away the secret it was used for
+    let mut random_result = if rng.is_cryptographically_secure() {
+        generate(settings, expected_rules, rng)
+    } else if settings.record_draws() || rng.seed().is_none() {
+        //Without a seed the draws are the only way to replay, they are kept when numbers were not created
+        let recording_rng = RecordingRandom::new(rng);
+        let mut random_result = generate(settings, expected_rules, &recording_rng);
+        random_result.replay_token = if settings.record_draws() || random_result.status != RandomResultType::Success {
+            recording_rng.replay_token()
+        } else {
+            ReplayToken::default()
+        };
+        random_result
+    } else {
+        let seed = rng.seed();
+        let mut random_result = generate(settings, expected_rules, rng);
+        random_result.replay_token = ReplayToken::new(seed, &[]);
+        random_result
+    };
+    if settings.min_entropy_bits().is_some() && settings.fixed_numbers().is_empty() && random_result.status == RandomResultType::Success {
+        match settings.entropy_with_rng(rng) {
+            Ok(entropy_report) => random_result.entropy = Some(entropy_report),
+            Err(e) => {
+                //The numbers were created, so the missing report is logged instead of failing them
+                let mut logs = VecDeque::from(std::mem::take(&mut random_result.logs));
+                push_log(&mut logs, settings, LogLevel::Error, || Log::Error {
+                    msg: format!("No entropy report for min_entropy_bits: {}", e),
+                });
+                random_result.logs = Vec::from(logs);
+                #[cfg(feature="tracing")]
+                tracing::warn!(reason = %e, "no entropy report for min_entropy_bits");
+            },
+        }
+    }
+    if let Some(observer) = settings.observer() {
+        observer.on_finish(&random_result);
+    }
+    #[cfg(feature="tracing")]
+    match &random_result.error {
+        Some(e) => tracing::warn!(status = ?random_result.status, attempts = random_result.attempts, error = %e, "numbers were not created"),
+        None => tracing::debug!(attempts = random_result.attempts, "numbers were created"),
+    }
+    return random_result;
+}
+
+pub fn complete_numbers(settings: &Settings, fixed_numbers: &[Option<usize>]) -> RandomResult {
+    return complete_numbers_with_rng(settings, fixed_numbers, get_random_trait().as_ref());
+}
+
+//Creates the numbers that are None in fixed_numbers, the numbers that are Some stay at their
+//index.  fixed_numbers can be shorter than count to give a prefix.  When the fixed numbers can
+//never match a rule the status is Infeasible with RandomError::FixedNumbersRejected.  Only the
+//incremental solver can complete numbers and there is no entropy report.
+pub fn complete_numbers_with_rng(settings: &Settings, fixed_numbers: &[Option<usize>], rng: &dyn RandomTrait) -> RandomResult {
+    return random_numbers_with_rules(&settings.with_fixed_numbers(fixed_numbers), &mut Vec::new(), rng);
+}
+
+pub fn reroll_numbers(settings: &Settings, numbers: &[usize], indexes: &[usize]) -> RandomResult {
+    return reroll_numbers_with_rng(settings, numbers, indexes, get_random_trait().as_ref());
+}
+
+//Creates new numbers at indexes and keeps the other numbers at their index, the new numbers
+//still match every rule.  A new number can be the same as the old one when nothing else matches.
+pub fn reroll_numbers_with_rng(settings: &Settings, numbers: &[usize], indexes: &[usize], rng: &dyn RandomTrait) -> RandomResult {
+    return random_numbers_with_rules(&settings.with_rerolled_numbers(numbers, indexes), &mut Vec::new(), rng);
+}
+
+//Err when rng is not cryptographically secure.  Only rng is checked, rules like
+//NumberPool::alphanumeric that draw when they are created have to be given the same rng.
+pub fn random_password(settings: &Settings, rng: &dyn RandomTrait) -> std::result::Result<String, RandomError> {
+    if !rng.is_cryptographically_secure() {
+        return Err(RandomError::InsecureRandom { rng: format!("{:?}", rng) });
+    }
+    return random_numbers_with_rng(settings, rng).string_with_rng(true, rng);
+}
+
+fn generate<'a>(
+    settings: &'a Settings,
+    expected_rules: &mut Vec<&'a dyn RuleTrait>,
+    rng: &dyn RandomTrait
+) -> RandomResult {
+    if !settings.bad_request_errors().is_empty() {
+        let mut logs: VecDeque<Log> = VecDeque::new();
+        for e in settings.bad_request_errors() {
+            push_log(&mut logs, settings, LogLevel::Error, || Log::Error { msg: e.to_owned() });
+        }
+        return RandomResult {
+            status: RandomResultType::BadRequest,
+            numbers: Vec::new(),
+            attempts: 0,
+            logs: Vec::from(logs),
+            clear_err_tracker: Vec::new(),
+            replay_token: ReplayToken::default(),
+            entropy: None,
+            trace: Vec::new(),
+            rule_stats: HashMap::new(),
+            error: Some(RandomError::BadRequest { errors: settings.bad_request_errors().clone() }),
+        };
+    }
+    if let Err(e) = settings.analyze() {
+        let mut logs: VecDeque<Log> = VecDeque::new();
+        push_log(&mut logs, settings, LogLevel::Error, || Log::Error { msg: e.to_string() });
+        return RandomResult {
+            status: RandomResultType::Infeasible,
+            numbers: Vec::new(),
+            attempts: 0,
+            logs: Vec::from(logs),
+            clear_err_tracker: Vec::new(),
+            replay_token: ReplayToken::default(),
+            entropy: None,
+            trace: Vec::new(),
+            rule_stats: HashMap::new(),
+            error: Some(e),
+        };
+    }
+    if settings.solver() == SolverType::Backtracking {
+        return solve(settings, expected_rules, rng);
+    }
+    if settings.solver() == SolverType::Uniform {
+        return sample_uniform(settings, expected_rules, rng);
+    }
     let mut numbers: Vec<usize> = Vec::new();
     let mut is_match_attempts: usize = 0;
     let mut err_tracker: HashMap<String, usize> = HashMap::new();
     let mut num_attempts = 1;
-    let mut logs: Vec<Log> = Vec::new();
+    let mut logs: VecDeque<Log> = VecDeque::new();
     let mut clear_err_tracker: Vec<usize> = Vec::new();
-    let mut expected_rules: Vec<Box<dyn RuleTrait>> = settings.expected_rules().clone();
+    let mut kept_numbers: Option<Vec<usize>> = None;
+    let mut failure_tracker: HashMap<String, usize> = HashMap::new();
+    let mut last_numbers: Vec<usize> = Vec::new();
+    expected_rules.clear();
+    expected_rules.extend(settings.expected_rules().iter().map(|x| x.as_ref()));
     let mut key_to_make_priority: Option<String> = None;
+    let mut trace = TraceRecorder::new(settings);
+    let mut rule_stats = RuleStatsRecorder::new(settings);
+    let deadline = Deadline::new(settings);
     clear_numbers(&mut logs, &mut numbers, settings);
+    if !settings.fixed_numbers().is_empty() {
+        let check = check_fixed_numbers(expected_rules, settings, &numbers, rng, &mut logs, &mut rule_stats);
+        if !matches!(check, Ok(false)) {
+            let error = check.err();
+            if let Some(e) = &error {
+                push_log(&mut logs, settings, LogLevel::Error, || Log::Error { msg: e.to_string() });
+            }
+            return RandomResult {
+                status: if error.is_some() { RandomResultType::Infeasible } else { RandomResultType::Success },
+                numbers: if error.is_some() { Vec::new() } else { numbers },
+                attempts: 0,
+                logs: Vec::from(logs),
+                clear_err_tracker,
+                replay_token: ReplayToken::default(),
+                entropy: None,
+                trace: trace.into_entries(),
+                rule_stats: rule_stats.into_rule_stats(),
+                error,
+            };
+        }
+    }
     for attempts in 1..=settings.max_tries() {
-        logs.push(Log::Info {
+        if let Some(stop_reason) = deadline.stop_reason() {
+            let error = stop_reason.error(attempts - 1, settings);
+            push_log(&mut logs, settings, LogLevel::Error, || Log::Error { msg: error.to_string() });
+            return RandomResult {
+                status: stop_reason.status(),
+                numbers: Vec::new(),
+                attempts: attempts - 1,
+                logs: Vec::from(logs),
+                clear_err_tracker,
+                replay_token: ReplayToken::default(),
+                entropy: None,
+                trace: trace.into_entries(),
+                rule_stats: rule_stats.into_rule_stats(),
+                error: Some(error),
+            };
+        }
+        push_log(&mut logs, settings, LogLevel::Info, || Log::Info {
             msg: format!("Attempt - {:?}", attempts),
         });
+        #[cfg(feature="tracing")]
+        let _attempt_span = tracing::debug_span!("attempt", attempt = attempts).entered();
+        trace.push(|| TraceEvent::AttemptStarted { attempt: attempts });
+        if let Some(observer) = settings.observer() {
+            observer.on_attempt(attempts);
+        }
         num_attempts = attempts;
         let mut potential_numbers = Vec::new();
         let mut gen_type = String::from("");
 
         let current_data_numbers: Vec<usize> = numbers.iter().copied().collect();
         let current_data_shared_data: HashMap<String, HashMap<String, MapAnyValue>> = HashMap::new();
-        let current_data_selected_numbers_sd = CurrentData::new(&current_data_numbers, settings, &current_data_shared_data);
+        let current_data_selected_numbers_sd = CurrentData::with_rng(&current_data_numbers, settings, &current_data_shared_data, rng);
         let mut shared_data: HashMap<String, HashMap<String, MapAnyValue>> = HashMap::new();
-        shuffle_vec(&mut expected_rules);
-        //expected_rules.shuffle(&mut thread_rng()); TODO remove
+        shuffle_vec_with_rng(expected_rules, rng);
         if let Some(v) = key_to_make_priority {
             let idx = expected_rules
                 .iter()
@@ -258,34 +701,43 @@ pub fn random_numbers(settings: &Settings) -> RandomResult {
             key_to_make_priority = None;
         }
 
-        for expected_rule in &expected_rules {
+        for expected_rule in expected_rules.iter() {
             if let Some(actual_rule_shared_data) =
-                expected_rule.share_data(&current_data_selected_numbers_sd)
+                rule_stats.time(|| expected_rule.name(), || expected_rule.share_data(&current_data_selected_numbers_sd))
             {
                 shared_data.insert(expected_rule.name(), actual_rule_shared_data);
             }
         }
 
         let current_data_selected_numbers_gn = CurrentData::from_current_data(&current_data_selected_numbers_sd, &shared_data);
-        for expected_rule in &expected_rules {
-            match expected_rule.get_numbers(&current_data_selected_numbers_gn) {
+        for expected_rule in expected_rules.iter() {
+            match rule_stats.time(|| expected_rule.name(), || expected_rule.get_numbers(&current_data_selected_numbers_gn)) {
                 Ok(v) => {
                     gen_type = expected_rule.name();
+                    rule_stats.add_generated(&gen_type, v.len());
                     potential_numbers.extend(&v);
                     break;
                 }
                 Err(e) => {
                     if e != "Skip" {
-                        halt_from_error(
-                            &format!("gn-{}", expected_rule.name()),
+                        let err_for_err_tracker = format!("gn-{}", expected_rule.name());
+                        let is_reset = halt_from_error_with_positions(
+                            &err_for_err_tracker,
                             &e,
                             &mut logs,
                             &mut err_tracker,
+                            &mut failure_tracker,
                             attempts,
                             &mut numbers,
                             settings,
                             &mut clear_err_tracker,
+                            &mut kept_numbers,
+                            || None,
                         );
+                        if is_reset {
+                            rule_stats.add_reset(rule_name_of_key(&err_for_err_tracker));
+                            trace.push(|| reset_event(attempts, &err_for_err_tracker, &e));
+                        }
                     }
                 }
             }
@@ -293,67 +745,107 @@ pub fn random_numbers(settings: &Settings) -> RandomResult {
 
         //Check if potential_numbers are valid
         if !potential_numbers.is_empty() {
-            let _temp_numbers = numbers
-            .iter()
-            .copied()
-            .chain(potential_numbers.iter().copied())
-            .collect::<Vec<usize>>();
-            let current_data_with_potential_numbers = CurrentData::new(&_temp_numbers, settings, &shared_data);
-
-            logs.push(Log::Info {
+            let mut _temp_numbers = numbers.to_vec();
+            extend_with_fixed(&mut _temp_numbers, &potential_numbers, settings);
+            let current_data_with_potential_numbers = CurrentData::with_rng(&_temp_numbers, settings, &shared_data, rng);
+            last_numbers.clone_from(&_temp_numbers);
+
+            push_log(&mut logs, settings, LogLevel::Trace, || Log::Trace {
                 msg: format!(
                     "GEN_TYPE - {}; P - {:?}; A&P - {:?}",
                     gen_type, potential_numbers, current_data_with_potential_numbers.selected_numbers
                 ),
             });
+            trace.push(|| TraceEvent::RuleGenerated {
+                attempt: attempts,
+                rule_name: gen_type.to_owned(),
+                numbers: potential_numbers.to_vec(),
+                selected: current_data_with_potential_numbers.selected_numbers().to_vec(),
+            });
+            if let Some(observer) = settings.observer() {
+                observer.on_candidate(attempts, &gen_type, &potential_numbers, current_data_with_potential_numbers.selected_numbers());
+            }
 
             if current_data_with_potential_numbers.selected_numbers().len() > settings.count() {
-                halt_from_error(
+                let is_reset = halt_from_error_with_positions(
                     "Too Many Numbers Selected.",
                     "Too Many Numbers Selected.",
                     &mut logs,
                     &mut err_tracker,
+                    &mut failure_tracker,
                     attempts,
                     &mut numbers,
                     settings,
                     &mut clear_err_tracker,
+                    &mut kept_numbers,
+                    || None,
                 );
+                if is_reset {
+                    rule_stats.add_reset(&gen_type);
+                    trace.push(|| reset_event(attempts, "Too Many Numbers Selected.", "Too Many Numbers Selected."));
+                }
                 continue;
             }
 
             if current_data_with_potential_numbers.selected_numbers().len() == settings.count() {
                 is_match_attempts += 1;
 
-                match is_match_check(&expected_rules, settings.exclude_rules(), settings, &current_data_with_potential_numbers, &mut logs) {
+                match is_match_check(expected_rules, settings.exclude_rules(), settings, &current_data_with_potential_numbers, &mut logs, &mut rule_stats) {
                     Ok(_) => {
                         set_numbers(
                             &mut numbers,
                             settings,
                             &current_data_with_potential_numbers.selected_numbers(),
                         );
+                        trace.push(|| TraceEvent::Success { attempt: attempts, numbers: numbers.to_vec() });
                         return RandomResult {
                             status: RandomResultType::Success,
                             numbers,
                             attempts,
-                            logs,
-                            clear_err_tracker
+                            logs: Vec::from(logs),
+                            clear_err_tracker,
+                            replay_token: ReplayToken::default(),
+                            entropy: None,
+                            trace: trace.into_entries(),
+                            rule_stats: rule_stats.into_rule_stats(),
+                            error: None,
                         };
                     },
                     Err(e) => {
-                        halt_from_error(
-                            &format!("imc-{}", e.1),
-                            &format!("is_match_check failed. {}", e.0),
+                        rule_stats.add_match_rejections(&e.1, 1);
+                        trace.push(|| TraceEvent::MatchFailed {
+                            attempt: attempts,
+                            rule_name: e.1.to_owned(),
+                            reason: e.0.to_owned(),
+                            numbers: current_data_with_potential_numbers.selected_numbers().to_vec(),
+                        });
+                        let err_for_err_tracker = format!("imc-{}", e.1);
+                        let err = format!("is_match_check failed. {}", e.0);
+                        let is_reset = halt_from_error_with_positions(
+                            &err_for_err_tracker,
+                            &err,
                             &mut logs,
                             &mut err_tracker,
+                            &mut failure_tracker,
                             attempts,
                             &mut numbers,
                             settings,
                             &mut clear_err_tracker,
+                            &mut kept_numbers,
+                            || violating_positions(expected_rules, &e.1, &current_data_with_potential_numbers),
                         );
-                        if is_match_attempts == settings.max_is_match_attempts() {                        
-                            clear_numbers(&mut logs, &mut numbers, settings);
+                        if is_reset {
+                            rule_stats.add_reset(&e.1);
+                            trace.push(|| reset_event(attempts, &err_for_err_tracker, &err));
+                        }
+                        if is_match_attempts == settings.max_is_match_attempts() {
+                            //A reset already removed numbers and the positions are of the numbers before it
+                            if !is_reset {
+                                let positions = violating_positions(expected_rules, &e.1, &current_data_with_potential_numbers);
+                                remove_numbers(&mut logs, &mut numbers, settings, &mut kept_numbers, positions);
+                            }
                             is_match_attempts = 0;
-                            logs.push(Log::Info {
+                            push_log(&mut logs, settings, LogLevel::Info, || Log::Info {
                                 msg: format!("Clear - Reached Is Match Attempts"),
                             });
                         }
@@ -361,7 +853,7 @@ pub fn random_numbers(settings: &Settings) -> RandomResult {
                     }                    
                 }
             } else {
-                match is_within_range_check(&expected_rules, settings.exclude_rules(), settings, &current_data_with_potential_numbers, &mut logs) {
+                match is_within_range_check(expected_rules, settings.exclude_rules(), settings, &current_data_with_potential_numbers, &mut logs, &mut rule_stats) {
                     Ok(_) => {
                         set_numbers(
                             &mut numbers,
@@ -370,19 +862,34 @@ pub fn random_numbers(settings: &Settings) -> RandomResult {
                         );
                     },
                     Err(e) => {
+                        rule_stats.add_within_range_rejection(&e.3);
+                        trace.push(|| TraceEvent::WithinRangeFailed {
+                            attempt: attempts,
+                            rule_name: e.3.to_owned(),
+                            reason: e.1.to_owned(),
+                            make_priority: e.0 == IsWithinErrorType::MakePriority,
+                        });
                         if e.0 == IsWithinErrorType::MakePriority {
                             key_to_make_priority = Some(e.2);
                         }
-                        halt_from_error(
-                            &format!("iwrc-{}", e.3),
+                        let err_for_err_tracker = format!("iwrc-{}", e.3);
+                        let is_reset = halt_from_error_with_positions(
+                            &err_for_err_tracker,
                             &e.1,
                             &mut logs,
                             &mut err_tracker,
+                            &mut failure_tracker,
                             attempts,
                             &mut numbers,
                             settings,
                             &mut clear_err_tracker,
+                            &mut kept_numbers,
+                            || violating_positions(expected_rules, &e.3, &current_data_with_potential_numbers),
                         );
+                        if is_reset {
+                            rule_stats.add_reset(&e.3);
+                            trace.push(|| reset_event(attempts, &err_for_err_tracker, &e.1));
+                        }
                         continue;
                     },
                 }
@@ -390,27 +897,144 @@ pub fn random_numbers(settings: &Settings) -> RandomResult {
         }
     }
 
+    let (rule_name, rule_failures) = most_failed_rule(&failure_tracker);
+    push_log(&mut logs, settings, LogLevel::Error, || Log::Error { msg: format!("Reached max_tries: {}", num_attempts) });
     return RandomResult {
         status: RandomResultType::Failed,
         numbers: Vec::new(),
         attempts: num_attempts,
-        logs,
-        clear_err_tracker
+        logs: Vec::from(logs),
+        error: Some(RandomError::MaxTriesExceeded {
+            attempts: num_attempts,
+            max_tries: settings.max_tries(),
+            rule_name,
+            rule_failures,
+            last_numbers,
+            resets: clear_err_tracker.len(),
+        }),
+        clear_err_tracker,
+        replay_token: ReplayToken::default(),
+        entropy: None,
+        trace: trace.into_entries(),
+        rule_stats: rule_stats.into_rule_stats(),
     };
 }
 
+fn solve<'a>(
+    settings: &'a Settings,
+    expected_rules: &mut Vec<&'a dyn RuleTrait>,
+    rng: &dyn RandomTrait
+) -> RandomResult {
+    expected_rules.clear();
+    expected_rules.extend(settings.expected_rules().iter().map(|x| x.as_ref()));
+    let mut solver = Solver::new(settings, expected_rules, rng);
+    let outcome = solver.solve();
+    let nodes = solver.nodes();
+    let numbers = match outcome {
+        SolverOutcome::Found(numbers) => Ok(numbers),
+        SolverOutcome::Infeasible(reason) => Err(RandomError::Infeasible { reason }),
+        SolverOutcome::BadRequest(e) => Err(RandomError::BadRequest { errors: vec![e] }),
+        SolverOutcome::NodeLimit { rule_name, rule_failures, last_numbers } => Err(RandomError::Undecided {
+            nodes,
+            max_solver_nodes: settings.max_solver_nodes().unwrap_or(nodes),
+            rule_name,
+            rule_failures,
+            last_numbers,
+        }),
+        SolverOutcome::Stopped(stop_reason) => Err(stop_reason.error(nodes, settings)),
+    };
+    return search_result(settings, format!("Nodes - {}", nodes), nodes, solver.failure_tracker(), numbers);
+}
+
+fn sample_uniform<'a>(
+    settings: &'a Settings,
+    expected_rules: &mut Vec<&'a dyn RuleTrait>,
+    rng: &dyn RandomTrait
+) -> RandomResult {
+    expected_rules.clear();
+    expected_rules.extend(settings.expected_rules().iter().map(|x| x.as_ref()));
+    let mut sampler = UniformSampler::new(settings, expected_rules, rng);
+    let outcome = sampler.sample();
+    let attempts = sampler.attempts();
+    let numbers = match outcome {
+        UniformOutcome::Found(numbers) => Ok(numbers),
+        UniformOutcome::Infeasible(reason) => Err(RandomError::Infeasible { reason }),
+        UniformOutcome::BadRequest(e) => Err(RandomError::BadRequest { errors: vec![e] }),
+        UniformOutcome::MaxTries { rule_name, rule_failures, last_numbers } => Err(RandomError::MaxTriesExceeded {
+            attempts,
+            max_tries: settings.max_tries(),
+            rule_name,
+            rule_failures,
+            last_numbers,
+            resets: 0,
+        }),
+        UniformOutcome::Stopped(stop_reason) => Err(stop_reason.error(attempts, settings)),
+    };
+    return search_result(settings, format!("Attempts - {}", attempts), attempts, sampler.failure_tracker(), numbers);
+}
+
+//Result of the solvers that check whole numbers, every failure of failure_tracker is a match rejection
+fn search_result(
+    settings: &Settings,
+    progress: String,
+    attempts: usize,
+    failure_tracker: &HashMap<String, usize>,
+    numbers: std::result::Result<Vec<usize>, RandomError>,
+) -> RandomResult {
+    let mut trace = TraceRecorder::new(settings);
+    let mut rule_stats = RuleStatsRecorder::new(settings);
+    for (rule_name, rule_failures) in failure_tracker {
+        rule_stats.add_match_rejections(rule_name, *rule_failures);
+    }
+    let mut logs: VecDeque<Log> = VecDeque::new();
+    push_log(&mut logs, settings, LogLevel::Info, || Log::Info { msg: format!("SOLVER - {:?}; {}", settings.solver(), progress) });
+    let (status, numbers, error) = match numbers {
+        Ok(numbers) => {
+            trace.push(|| TraceEvent::Success { attempt: attempts, numbers: numbers.to_vec() });
+            (RandomResultType::Success, numbers, None)
+        },
+        Err(e) => {
+            push_log(&mut logs, settings, LogLevel::Error, || Log::Error { msg: e.to_string() });
+            (error_status(&e), Vec::new(), Some(e))
+        },
+    };
+    return RandomResult {
+        status,
+        numbers,
+        attempts,
+        logs: Vec::from(logs),
+        clear_err_tracker: Vec::new(),
+        replay_token: ReplayToken::default(),
+        entropy: None,
+        trace: trace.into_entries(),
+        rule_stats: rule_stats.into_rule_stats(),
+        error,
+    };
+}
+
+fn error_status(error: &RandomError) -> RandomResultType {
+    return match error {
+        RandomError::BadRequest { .. } => RandomResultType::BadRequest,
+        RandomError::Infeasible { .. } | RandomError::FixedNumbersRejected { .. } => RandomResultType::Infeasible,
+        RandomError::TimedOut { .. } => RandomResultType::TimedOut,
+        RandomError::Cancelled { .. } => RandomResultType::Cancelled,
+        RandomError::Undecided { .. } => RandomResultType::Undecided,
+        _ => RandomResultType::Failed,
+    };
+}
 
 fn is_match_check(
-    expected_rules: &[Box<dyn RuleTrait>], 
+    expected_rules: &[&dyn RuleTrait], 
     exclude_rules: &Option<Vec<Box<dyn ExcludeRuleTrait>>>, 
-    _settings: &Settings, current_data: &CurrentData,
-    logs: &mut Vec<Log>) -> std::result::Result<(), (String, String)> {
+    settings: &Settings, current_data: &CurrentData,
+    logs: &mut VecDeque<Log>,
+    rule_stats: &mut RuleStatsRecorder) -> std::result::Result<(), (String, String)> {
     let mut err: std::result::Result<(), String> = Ok(());
     let mut rule_name = String::new();
     for expected_rule in expected_rules {
-        err = expected_rule.is_match(current_data);
+        err = rule_stats.time(|| expected_rule.name(), || expected_rule.is_match(current_data));
         if err.is_err() {
-            logs.push(Log::Info {
+            push_log(logs, settings, LogLevel::Trace, || Log::Trace {
                 msg: format!("Expected Rule - {} - {}", expected_rule.name(), expected_rule),
             });
             rule_name = expected_rule.name();
@@ -420,9 +1044,9 @@ fn is_match_check(
     if let Ok(()) = err {
         if let Some(exc_rules) = exclude_rules {
             for exclude_rule in exc_rules {
-                err = exclude_rule.is_excluded(current_data);
+                err = rule_stats.time(|| format!("exr-{}", exclude_rule.exclude_name()), || exclude_rule.is_excluded(current_data));
                 if err.is_err() {
-                    logs.push(Log::Info {
+                    push_log(logs, settings, LogLevel::Trace, || Log::Trace {
                         msg: format!("Exclude Rule - {} - {}", exclude_rule.exclude_name(), exclude_rule),
                     });
                     rule_name = format!("exr-{}", exclude_rule.exclude_name());
@@ -441,18 +1065,19 @@ fn is_match_check(
 }
 
 fn is_within_range_check(
-    expected_rules: &[Box<dyn RuleTrait>], 
+    expected_rules: &[&dyn RuleTrait], 
     exclude_rules: &Option<Vec<Box<dyn ExcludeRuleTrait>>>, 
-    _settings: &Settings, 
+    settings: &Settings, 
     current_data: &CurrentData,
-    logs: &mut Vec<Log>) -> std::result::Result<(), (IsWithinErrorType, String, String, String)> {
+    logs: &mut VecDeque<Log>,
+    rule_stats: &mut RuleStatsRecorder) -> std::result::Result<(), (IsWithinErrorType, String, String, String)> {
     let mut err: std::result::Result<(), (IsWithinErrorType, String)> = Ok(());
     let mut priority_rule_name = String::new();
     let mut rule_name = String::new();
     for expected_rule in expected_rules {
-        err = expected_rule.is_within_range(current_data);
+        err = rule_stats.time(|| expected_rule.name(), || expected_rule.is_within_range(current_data));
         if err.is_err() {
-            logs.push(Log::Info {
+            push_log(logs, settings, LogLevel::Trace, || Log::Trace {
                 msg: format!("Expected Rule - {} - {}", expected_rule.name(), expected_rule),
             });
             priority_rule_name = expected_rule.name();
@@ -463,9 +1088,12 @@ fn is_within_range_check(
     if let Ok(()) = err {
         if let Some(exc_rules) = exclude_rules {
             for exclude_rule in exc_rules {
-                err = exclude_rule.is_within_excluded_range(current_data);
+                err = rule_stats.time(
+                    || format!("exr-{}", exclude_rule.exclude_name()),
+                    || exclude_rule.is_within_excluded_range(current_data)
+                );
                 if err.is_err() {
-                    logs.push(Log::Info {
+                    push_log(logs, settings, LogLevel::Trace, || Log::Trace {
                         msg: format!("Exclude Rule - {} - {}", exclude_rule.exclude_name(), exclude_rule),
                     });
                     priority_rule_name = exclude_rule.exclude_name();
@@ -483,3 +1111,58 @@ fn is_within_range_check(
         }
     };
 }
+
+//Error tracker keys are "<check>-<rule name>"
+fn rule_name_of_key(key: &str) -> &str {
+    return match key.split_once('-') {
+        Some((_, v)) => v,
+        None => key,
+    };
+}
+
+fn rejection_kind_of_key(key: &str) -> RejectionKind {
+    return match key.split_once('-') {
+        Some(("gn", _)) => RejectionKind::GetNumbers,
+        Some(("iwrc", _)) => RejectionKind::WithinRange,
+        Some(("imc", _)) => RejectionKind::Match,
+        _ => RejectionKind::TooManyNumbers,
+    };
+}
+
+fn reset_event(attempt: usize, err_for_err_tracker: &str, err: &str) -> TraceEvent {
+    return TraceEvent::Reset { attempt, rule_name: rule_name_of_key(err_for_err_tracker).to_owned(), reason: err.to_owned() };
+}
+
+//Add up the failures of every check by rule name
+fn most_failed_rule(failure_tracker: &HashMap<String, usize>) -> (Option<String>, usize) {
+    let mut failures_by_rule: HashMap<&str, usize> = HashMap::new();
+    for (key, count) in failure_tracker {
+        *failures_by_rule.entry(rule_name_of_key(key)).or_insert(0) += count;
+    }
+    return max_failures(failures_by_rule);
+}
+
+//Rule name with the most failures, the first name wins a tie so the result does not depend on
+//the order of a HashMap
+pub(crate) fn max_failures<'a>(failures: impl IntoIterator<Item = (&'a str, usize)>) -> (Option<String>, usize) {
+    return match failures.into_iter().max_by(|a, b| a.1.cmp(&b.1).then(b.0.cmp(a.0))) {
+        Some((rule_name, count)) => (Some(rule_name.to_owned()), count),
+        None => (None, 0),
+    };
+}
+
+//First rule that all numbers do not match and the reason, exclude rules are named
+//"exr-<exclude name>".  Used by the solvers that check whole numbers.
+pub(crate) fn failed_rule(expected_rules: &[&dyn RuleTrait], current_data: &CurrentData) -> Option<(String, String)> {
+    for expected_rule in expected_rules {
+        if let Err(e) = expected_rule.is_match(current_data) {
+            return Some((expected_rule.name(), e));
+        }
+    }
+    for exclude_rule in current_data.settings().exclude_rules().iter().flatten() {
+        if let Err(e) = exclude_rule.is_excluded(current_data) {
+            return Some((format!("exr-{}", exclude_rule.exclude_name()), e));
+        }
+    }
+    return None;
+}
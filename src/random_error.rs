@@ -0,0 +1,114 @@
+use std::fmt;
+use std::fmt::{Display, Formatter};
+use std::time::Duration;
+
+//A rule that numbers do not match, exclude rules are named "exr-<exclude name>"
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct RuleViolation {
+    rule_name: String,
+    message: String,
+}
+
+impl RuleViolation {
+    pub fn new(rule_name: &str, message: &str) -> RuleViolation {
+        return RuleViolation { rule_name: rule_name.to_owned(), message: message.to_owned() };
+    }
+
+    pub fn rule_name(&self) -> &str {
+        return &self.rule_name;
+    }
+
+    pub fn message(&self) -> &str {
+        return &self.message;
+    }
+}
+
+impl Display for RuleViolation {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        write!(f, "{}: {}", self.rule_name, self.message)
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum RandomError {
+    BadRequest {
+        errors: Vec<String>,
+    },
+    MaxTriesExceeded {
+        attempts: usize,
+        max_tries: usize,
+        rule_name: Option<String>,
+        rule_failures: usize,
+        last_numbers: Vec<usize>,
+        resets: usize,
+    },
+    InvalidCharCode {
+        number: usize,
+        idx: usize,
+    },
+    InsecureRandom {
+        rng: String,
+    },
+    Infeasible {
+        reason: String,
+    },
+    Uncountable {
+        reason: String,
+    },
+    TimedOut {
+        attempts: usize,
+        timeout: Duration,
+    },
+    Cancelled {
+        attempts: usize,
+    },
+    //The fixed numbers of random::complete_numbers can never match the rule
+    FixedNumbersRejected {
+        rule_name: String,
+        reason: String,
+    },
+    //The backtracking solver reached max_solver_nodes before it found numbers or proved there are none
+    Undecided {
+        nodes: usize,
+        max_solver_nodes: usize,
+        rule_name: Option<String>,
+        rule_failures: usize,
+        last_numbers: Vec<usize>,
+    },
+    //Every rule Settings::validate found that the numbers do not match
+    RuleViolations {
+        violations: Vec<RuleViolation>,
+    },
+}
+
+impl Display for RandomError {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self {
+            RandomError::BadRequest { errors } => write!(f, "Bad Request: {}", errors.join("; ")),
+            RandomError::MaxTriesExceeded { attempts, max_tries, rule_name, rule_failures, last_numbers, resets } => write!(
+                f,
+                "Could not create numbers after {} of {} attempts and {} resets. Rule that failed the most: {} ({} times). Last numbers: {:?}",
+                attempts, max_tries, resets, rule_name.as_deref().unwrap_or("None"), rule_failures, last_numbers
+            ),
+            RandomError::InvalidCharCode { number, idx } => write!(f, "Number {} at index {} is not a valid char code", number, idx),
+            RandomError::InsecureRandom { rng } => write!(f, "{} is not cryptographically secure", rng),
+            RandomError::Infeasible { reason } => write!(f, "Infeasible: {}", reason),
+            RandomError::Uncountable { reason } => write!(f, "Could not count the matching numbers: {}", reason),
+            RandomError::TimedOut { attempts, timeout } => write!(f, "Timed out after {} attempts, timeout: {:?}", attempts, timeout),
+            RandomError::Cancelled { attempts } => write!(f, "Cancelled after {} attempts", attempts),
+            RandomError::FixedNumbersRejected { rule_name, reason } => write!(f, "Fixed numbers are rejected by {}: {}", rule_name, reason),
+            RandomError::Undecided { nodes, max_solver_nodes, rule_name, rule_failures, last_numbers } => write!(
+                f,
+                "Undecided after {} of {} solver nodes. Rule that failed the most: {} ({} times). Last numbers: {:?}",
+                nodes, max_solver_nodes, rule_name.as_deref().unwrap_or("None"), rule_failures, last_numbers
+            ),
+            RandomError::RuleViolations { violations } => write!(
+                f,
+                "Numbers do not match {} rules: {}",
+                violations.len(), violations.iter().map(|x| x.to_string()).collect::<Vec<String>>().join("; ")
+            ),
+        }
+    }
+}
+
+impl std::error::Error for RandomError {}
@@ -28,7 +28,11 @@ pub fn set_random_trait(random_trait: Box<dyn RandomTrait + Send + Sync>) {
 }
 
 pub fn get_random_vec_item<T>(vec: &[T]) -> &T {
-    return &vec[get_random_trait().get_number(0, vec.len() - 1)];
+    return get_random_vec_item_with_rng(vec, get_random_trait().as_ref());
+}
+
+pub fn get_random_vec_item_with_rng<'a, T>(vec: &'a [T], rng: &dyn RandomTrait) -> &'a T {
+    return &vec[rng.get_number(0, vec.len() - 1)];
 }
 
 // pub fn shuffle<T>(list: &mut [T]) {
@@ -37,16 +41,54 @@ pub fn get_random_vec_item<T>(vec: &[T]) -> &T {
 
 pub fn shuffle_vec<T>(vector: &mut [T])
 {
+    shuffle_vec_with_rng(vector, get_random_trait().as_ref());
+}
+
+pub fn shuffle_vec_with_rng<T>(vector: &mut [T], rng: &dyn RandomTrait)
+{
+    if vector.is_empty() {
+        return;
+    }
     let len = vector.len() - 1;
     for i in 0..=len {
-        vector.swap(i, get_random_trait().get_number(i, len));
+        vector.swap(i, rng.get_number(i, len));
     }
 }
 
 
+//Implement next_u64, get_number and get_bool are unbiased and built from it by default.  A
+//generator that has a faster way to draw a range or a bool can override them too.
 pub trait RandomTrait: RandomTraitClone + std::fmt::Debug {
-    fn get_number(&self, min: usize, max: usize) -> usize;
-    fn get_bool(&self) -> bool;
+    fn next_u64(&self) -> u64;
+
+    fn get_number(&self, min: usize, max: usize) -> usize {
+        let range = ((max - min) as u64).wrapping_add(1);
+        if range == 0 {
+            return self.next_u64() as usize;
+        }
+        //Reject the values that would make the modulo biased
+        let threshold = range.wrapping_neg() % range;
+        loop {
+            let value = self.next_u64();
+            if value >= threshold {
+                return min + (value % range) as usize;
+            }
+        }
+    }
+
+    fn get_bool(&self) -> bool {
+        return self.next_u64() >> 63 == 1;
+    }
+
+    //Seed that recreates this generator at its current position, if it has one
+    fn seed(&self) -> Option<u64> {
+        return None;
+    }
+
+    //Only generators backed by a cryptographically secure source should return true
+    fn is_cryptographically_secure(&self) -> bool {
+        return false;
+    }
 }
 
 pub trait RandomTraitClone {
@@ -67,4 +109,4 @@ impl Clone for Box<dyn RandomTrait> {
     fn clone(&self) -> Box<dyn RandomTrait> {
         self.clone_box()
     }
-}
\ No newline at end of file
+}
@@ -0,0 +1,264 @@
+use crate::random::{random_numbers_with_rng, RandomResult};
+use crate::random_trait::RandomTrait;
+use crate::seeded_random::SeededRandom;
+use crate::settings::Settings;
+use std::cell::{Cell, RefCell};
+use std::fmt;
+use std::fmt::{Display, Formatter};
+use std::str::FromStr;
+
+#[derive(Debug, PartialEq, Eq, Copy, Clone)]
+pub enum Draw {
+    Number { min: usize, max: usize, value: usize },
+    Bool(bool),
+    U64(u64),
+}
+
+impl Display for Draw {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self {
+            Draw::Number { min, max, value } => write!(f, "n{}-{}:{}", min, max, value),
+            Draw::Bool(value) => write!(f, "b{}", if *value { 1 } else { 0 }),
+            Draw::U64(value) => write!(f, "u{}", value),
+        }
+    }
+}
+
+impl FromStr for Draw {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Draw, String> {
+        let err = || format!("Invalid draw: {:?}", s);
+        if let Some(v) = s.strip_prefix('b') {
+            return match v {
+                "1" => Ok(Draw::Bool(true)),
+                "0" => Ok(Draw::Bool(false)),
+                _ => Err(err()),
+            };
+        }
+        if let Some(v) = s.strip_prefix('u') {
+            return Ok(Draw::U64(v.parse().map_err(|_| err())?));
+        }
+        let v = s.strip_prefix('n').ok_or_else(err)?;
+        let (range, value) = v.split_once(':').ok_or_else(err)?;
+        let (min, max) = range.split_once('-').ok_or_else(err)?;
+        return Ok(Draw::Number {
+            min: min.parse().map_err(|_| err())?,
+            max: max.parse().map_err(|_| err())?,
+            value: value.parse().map_err(|_| err())?,
+        });
+    }
+}
+
+//Everything needed to re-execute a generation run: the seed of the generator (when it has one)
+//and every draw that was taken from it when SettingsBuilder::record_draws is set.  Display and
+//FromStr round trip so a token can be copied from a log into a test.
+#[derive(Debug, PartialEq, Eq, Clone, Default)]
+pub struct ReplayToken {
+    seed: Option<u64>,
+    draws: Vec<Draw>,
+}
+
+impl ReplayToken {
+    pub fn new(seed: Option<u64>, draws: &[Draw]) -> ReplayToken {
+        return ReplayToken { seed, draws: draws.to_vec() };
+    }
+
+    pub fn seed(&self) -> Option<u64> {
+        return self.seed;
+    }
+
+    pub fn draws(&self) -> &Vec<Draw> {
+        return &self.draws;
+    }
+}
+
+impl Display for ReplayToken {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        if let Some(seed) = self.seed {
+            write!(f, "{}", seed)?;
+        }
+        write!(f, "|{}", self.draws.iter().map(|x| x.to_string()).collect::<Vec<String>>().join(","))
+    }
+}
+
+impl FromStr for ReplayToken {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<ReplayToken, String> {
+        let (seed, draws) = s.trim().split_once('|').ok_or_else(|| format!("Invalid replay token: {:?}", s))?;
+        return Ok(ReplayToken {
+            seed: if seed.is_empty() {
+                None
+            } else {
+                Some(seed.parse().map_err(|_| format!("Invalid replay token seed: {:?}", seed))?)
+            },
+            draws: if draws.is_empty() {
+                Vec::new()
+            } else {
+                draws.split(',').map(Draw::from_str).collect::<std::result::Result<Vec<Draw>, String>>()?
+            },
+        });
+    }
+}
+
+//Passes every draw through to the wrapped generator and remembers it
+#[derive(Debug)]
+pub struct RecordingRandom<'a> {
+    rng: &'a dyn RandomTrait,
+    seed: Option<u64>,
+    draws: RefCell<Vec<Draw>>,
+}
+
+impl<'a> RecordingRandom<'a> {
+    pub fn new(rng: &'a dyn RandomTrait) -> RecordingRandom<'a> {
+        return RecordingRandom { rng, seed: rng.seed(), draws: RefCell::new(Vec::new()) };
+    }
+
+    pub fn replay_token(&self) -> ReplayToken {
+        return ReplayToken { seed: self.seed, draws: self.draws.borrow().clone() };
+    }
+}
+
+impl crate::random_trait::RandomTraitClone for RecordingRandom<'_> {
+    fn clone_box(&self) -> Box<dyn RandomTrait> {
+        return self.rng.clone_box();
+    }
+}
+
+impl RandomTrait for RecordingRandom<'_> {
+    fn next_u64(&self) -> u64 {
+        let value = self.rng.next_u64();
+        self.draws.borrow_mut().push(Draw::U64(value));
+        return value;
+    }
+
+    fn get_number(&self, min: usize, max: usize) -> usize {
+        let value = self.rng.get_number(min, max);
+        self.draws.borrow_mut().push(Draw::Number { min, max, value });
+        return value;
+    }
+
+    fn get_bool(&self) -> bool {
+        let value = self.rng.get_bool();
+        self.draws.borrow_mut().push(Draw::Bool(value));
+        return value;
+    }
+
+    fn seed(&self) -> Option<u64> {
+        return self.rng.seed();
+    }
+
+    fn is_cryptographically_secure(&self) -> bool {
+        return self.rng.is_cryptographically_secure();
+    }
+}
+
+//Hands out the draws of a ReplayToken in order.  Asking for a draw that was not recorded means
+//the run went down a different path, which is kept in diverged() and the closest value is returned.
+#[derive(Debug, Clone)]
+pub struct ReplayRandom {
+    seed: Option<u64>,
+    draws: Vec<Draw>,
+    idx: Cell<usize>,
+    diverged: RefCell<Option<String>>,
+}
+
+impl ReplayRandom {
+    pub fn new(replay_token: &ReplayToken) -> ReplayRandom {
+        return ReplayRandom { seed: replay_token.seed, draws: replay_token.draws.clone(), idx: Cell::new(0), diverged: RefCell::new(None) };
+    }
+
+    pub fn diverged(&self) -> Option<String> {
+        return self.diverged.borrow().clone();
+    }
+
+    fn next_draw(&self, expected: &str) -> Option<Draw> {
+        let idx = self.idx.get();
+        self.idx.set(idx + 1);
+        let draw = self.draws.get(idx).copied();
+        if draw.is_none() {
+            self.set_diverged(format!("Draw {} ({}) was not recorded, only {} draws exist", idx, expected, self.draws.len()));
+        }
+        return draw;
+    }
+
+    fn set_diverged(&self, msg: String) {
+        let mut diverged = self.diverged.borrow_mut();
+        if diverged.is_none() {
+            *diverged = Some(msg);
+        }
+    }
+}
+
+impl RandomTrait for ReplayRandom {
+    fn next_u64(&self) -> u64 {
+        return match self.next_draw("u") {
+            Some(Draw::U64(value)) => value,
+            Some(draw) => {
+                self.set_diverged(format!("Draw {} expected u but recorded {}", self.idx.get() - 1, draw));
+                0
+            },
+            None => 0,
+        };
+    }
+
+    fn get_number(&self, min: usize, max: usize) -> usize {
+        let expected = format!("n{}-{}", min, max);
+        return match self.next_draw(&expected) {
+            Some(Draw::Number { min: d_min, max: d_max, value }) if d_min == min && d_max == max => value,
+            Some(draw) => {
+                self.set_diverged(format!("Draw {} expected {} but recorded {}", self.idx.get() - 1, expected, draw));
+                min
+            },
+            None => min,
+        };
+    }
+
+    fn get_bool(&self) -> bool {
+        return match self.next_draw("b") {
+            Some(Draw::Bool(value)) => value,
+            Some(draw) => {
+                self.set_diverged(format!("Draw {} expected b but recorded {}", self.idx.get() - 1, draw));
+                false
+            },
+            None => false,
+        };
+    }
+
+    fn seed(&self) -> Option<u64> {
+        return self.seed;
+    }
+}
+
+//A token with only a seed is replayed with SeededRandom, use replay_with for other generators
+pub fn replay(settings: &Settings, replay_token: &ReplayToken) -> std::result::Result<RandomResult, String> {
+    return replay_with(settings, replay_token, |seed| Box::new(SeededRandom::new(seed)));
+}
+
+//new_rng creates the generator that made the token from its seed, it is only called when the
+//token has no draws
+pub fn replay_with(
+    settings: &Settings,
+    replay_token: &ReplayToken,
+    new_rng: impl Fn(u64) -> Box<dyn RandomTrait>
+) -> std::result::Result<RandomResult, String> {
+    if replay_token.draws.is_empty() {
+        return match replay_token.seed {
+            Some(seed) => Ok(random_numbers_with_rng(settings, new_rng(seed).as_ref())),
+            None => Err("The replay token has no seed and no draws, set SettingsBuilder::record_draws for a generator without a seed".to_owned()),
+        };
+    }
+    let replay_random = ReplayRandom::new(replay_token);
+    let random_result = random_numbers_with_rng(settings, &replay_random);
+    if let Some(e) = replay_random.diverged() {
+        return Err(format!("Replay diverged from the recorded run. {}", e));
+    }
+    if replay_random.idx.get() != replay_token.draws.len() {
+        return Err(format!(
+            "Replay diverged from the recorded run. Used {} of {} recorded draws",
+            replay_random.idx.get(), replay_token.draws.len()
+        ));
+    }
+    return Ok(random_result);
+}
@@ -0,0 +1,90 @@
+use crate::settings::Settings;
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+//What one rule did while numbers were created.  Exclude rules are named "exr-<exclude name>".
+#[derive(Debug, Default, PartialEq, Eq, Clone)]
+pub struct RuleStats {
+    generated: usize,
+    within_range_rejections: usize,
+    match_rejections: usize,
+    resets: usize,
+    time: Duration,
+}
+
+impl RuleStats {
+    //Numbers the rule picked with get_numbers
+    pub fn generated(&self) -> usize {
+        return self.generated;
+    }
+
+    //Numbers turned down by is_within_range before all numbers were picked
+    pub fn within_range_rejections(&self) -> usize {
+        return self.within_range_rejections;
+    }
+
+    //Numbers turned down by is_match once all numbers were picked
+    pub fn match_rejections(&self) -> usize {
+        return self.match_rejections;
+    }
+
+    //Times numbers were removed because the rule failed too many times in a row
+    pub fn resets(&self) -> usize {
+        return self.resets;
+    }
+
+    //Time spent in the rule, only measured with SettingsBuilder::time_rules by the incremental solver
+    pub fn time(&self) -> Duration {
+        return self.time;
+    }
+}
+
+pub(crate) struct RuleStatsRecorder {
+    time_rules: bool,
+    rule_stats: HashMap<String, RuleStats>,
+}
+
+impl RuleStatsRecorder {
+    pub(crate) fn new(settings: &Settings) -> RuleStatsRecorder {
+        return RuleStatsRecorder { time_rules: settings.time_rules(), rule_stats: HashMap::new() };
+    }
+
+    //Runs f and adds the time it took to the rule, rule_name is only called when rules are timed
+    pub(crate) fn time<T>(&mut self, rule_name: impl FnOnce() -> String, f: impl FnOnce() -> T) -> T {
+        if !self.time_rules {
+            return f();
+        }
+        let start = Instant::now();
+        let value = f();
+        self.get_mut(&rule_name()).time += start.elapsed();
+        return value;
+    }
+
+    pub(crate) fn add_generated(&mut self, rule_name: &str, generated: usize) {
+        self.get_mut(rule_name).generated += generated;
+    }
+
+    pub(crate) fn add_within_range_rejection(&mut self, rule_name: &str) {
+        self.get_mut(rule_name).within_range_rejections += 1;
+    }
+
+    pub(crate) fn add_match_rejections(&mut self, rule_name: &str, match_rejections: usize) {
+        self.get_mut(rule_name).match_rejections += match_rejections;
+    }
+
+    pub(crate) fn add_reset(&mut self, rule_name: &str) {
+        self.get_mut(rule_name).resets += 1;
+    }
+
+    //Only allocates the name the first time the rule is seen
+    fn get_mut(&mut self, rule_name: &str) -> &mut RuleStats {
+        if !self.rule_stats.contains_key(rule_name) {
+            self.rule_stats.insert(rule_name.to_owned(), RuleStats::default());
+        }
+        return self.rule_stats.get_mut(rule_name).unwrap();
+    }
+
+    pub(crate) fn into_rule_stats(self) -> HashMap<String, RuleStats> {
+        return self.rule_stats;
+    }
+}
@@ -2,7 +2,7 @@ use crate::random::CurrentData;
 use std::any::Any;
 use std::fmt::Display;
 
-use super::IsWithinErrorType;
+use super::{IsWithinErrorType, MaybeSendSync};
 
 pub fn is_excluded_helper(is_match_results: &std::result::Result<(), String>, rule_str: &str) -> std::result::Result<(), String> {
     return match is_match_results {
@@ -11,7 +11,7 @@ pub fn is_excluded_helper(is_match_results: &std::result::Result<(), String>, ru
     };
 }
 
-pub trait ExcludeRuleTrait: ExcludeRuleTraitClone + Display + std::fmt::Debug {
+pub trait ExcludeRuleTrait: ExcludeRuleTraitClone + Display + std::fmt::Debug + MaybeSendSync {
 
     fn as_any(&self) -> &dyn Any;
 
@@ -26,6 +26,11 @@ pub trait ExcludeRuleTrait: ExcludeRuleTraitClone + Display + std::fmt::Debug {
     ) -> std::result::Result<(), String>;
 
     fn exclude_name(&self) -> String;
+
+    //True when the order of the numbers does not change is_excluded
+    fn is_order_independent(&self) -> bool {
+        return false;
+    }
 }
 
 pub trait ExcludeRuleTraitClone {
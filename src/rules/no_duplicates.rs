@@ -72,6 +72,40 @@ impl RuleTrait for NoDuplicate {
     ) -> std::result::Result<bool, String> {
         return Ok(true);
     }
+
+    fn prune_domain(
+        &self,
+        current_data: &CurrentData,
+        domain: &mut Vec<usize>,
+    ) {
+        domain.retain(|x| !current_data.selected_numbers_set().contains(x));
+    }
+
+    fn is_order_independent(&self) -> bool {
+        return true;
+    }
+
+    //Every copy of a number but one, the one that stays is a fixed number when there is one and
+    //else the first one
+    fn violating_positions(
+        &self,
+        current_data: &CurrentData,
+    ) -> Option<Vec<usize>> {
+        let settings = current_data.settings();
+        let mut kept: HashMap<usize, usize> = HashMap::new();
+        for (idx, number) in current_data.selected_numbers().iter().enumerate() {
+            match kept.get(number) {
+                Some(v) if settings.fixed_number(*v).is_some() || settings.fixed_number(idx).is_none() => {},
+                _ => {
+                    kept.insert(*number, idx);
+                },
+            }
+        }
+        return Some(current_data.selected_numbers().iter().enumerate()
+            .filter(|(idx, x)| kept[*x] != *idx)
+            .map(|(idx, _)| idx)
+            .collect());
+    }
 }
 
 impl ExcludeRuleTrait for NoDuplicate {
@@ -96,4 +130,8 @@ impl ExcludeRuleTrait for NoDuplicate {
     fn exclude_name(&self) -> String {
         return self.name();
     }
+
+    fn is_order_independent(&self) -> bool {
+        return true;
+    }
 }
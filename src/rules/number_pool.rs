@@ -1,5 +1,5 @@
 use crate::random::CurrentData;
-use crate::random_trait::{get_random_trait, get_random_vec_item};
+use crate::random_trait::{get_random_trait, get_random_vec_item_with_rng, RandomTrait};
 use crate::rules::{IsWithinErrorType, MapAnyValue, RuleTrait};
 use std::any::Any;
 use std::collections::{HashMap, HashSet};
@@ -35,13 +35,19 @@ impl PoolType {
     }
 
     pub fn random_number(&self) -> usize {
+        return self.random_number_with_rng(get_random_trait().as_ref());
+    }
+
+    pub fn random_number_with_rng(&self, rng: &dyn RandomTrait) -> usize {
         match self {
             PoolType::Set(set) => {
-                let rand_pool: Vec<usize> = set.iter().copied().collect();
-                return *get_random_vec_item(&rand_pool);
+                //Sorted so the same rng draws always pick the same number
+                let mut rand_pool: Vec<usize> = set.iter().copied().collect();
+                rand_pool.sort_unstable();
+                return *get_random_vec_item_with_rng(&rand_pool, rng);
             }
             PoolType::MinMax(min, max) => {
-                return get_random_trait().get_number(*min, *max);
+                return rng.get_number(*min, *max);
             }
         }
     }
@@ -97,6 +103,32 @@ impl PoolType {
         return self.len() == 0;
     }
 
+    //Smallest and largest number of the pool, None when the pool is empty
+    pub fn bounds(&self) -> Option<(usize, usize)> {
+        match self {
+            PoolType::Set(set) => {
+                return Some((*set.iter().min()?, *set.iter().max()?));
+            }
+            PoolType::MinMax(min, max) => {
+                return if min <= max { Some((*min, *max)) } else { None };
+            }
+        }
+    }
+
+    pub fn is_disjoint(&self, other: &PoolType) -> bool {
+        match (self, other) {
+            (PoolType::Set(set), PoolType::Set(other_set)) => {
+                return set.is_disjoint(other_set);
+            }
+            (PoolType::Set(set), other) | (other, PoolType::Set(set)) => {
+                return !set.iter().any(|x| other.contains(*x));
+            }
+            (PoolType::MinMax(min, max), PoolType::MinMax(other_min, other_max)) => {
+                return max < other_min || other_max < min;
+            }
+        }
+    }
+
     pub fn len(&self) -> usize {
         match self {
             PoolType::Set(set) => set.len(),
@@ -158,7 +190,14 @@ pub struct NumberPool {
 }
 
 impl NumberPool {
+    //How many letters, numbers and special characters there are is picked with the default random
+    //number generator, which is not cryptographically secure.  Use alphanumeric_with_rng with the
+    //rng of random::random_password for passwords.
     pub fn alphanumeric(count: usize, include_special_char: bool) -> NumberPool {
+        return NumberPool::alphanumeric_with_rng(count, include_special_char, get_random_trait().as_ref());
+    }
+
+    pub fn alphanumeric_with_rng(count: usize, include_special_char: bool, rng: &dyn RandomTrait) -> NumberPool {
         if (!include_special_char && count < 2) || (include_special_char && count < 3) {
             panic!("Count Must be {} or More", if include_special_char { 3 } else { 2 });
         }
@@ -166,11 +205,11 @@ impl NumberPool {
         let mut special_char_count: usize = if include_special_char { 1 } else { 0 };
 
         if count >= 10 {
-            numeric_count = get_random_trait().get_number(1, 3);
+            numeric_count = rng.get_number(1, 3);
         }
 
         if include_special_char && count >= 10 {
-            special_char_count = get_random_trait().get_number(1, 2);
+            special_char_count = rng.get_number(1, 2);
         }
 
         let alpha_count = count - numeric_count - special_char_count;
@@ -330,14 +369,19 @@ impl RuleTrait for NumberPool {
         let other_number_pool =
             NumberPool::from_numbers_2(&self.number_pool_items, current_data.selected_numbers_set());
         let mut numbers: Vec<usize> = Vec::new();
-        for (_key, number_pool_item) in &other_number_pool.number_pool_items {
+        let mut keys: Vec<&String> = other_number_pool.number_pool_items.keys().collect();
+        keys.sort_unstable();
+        for key in keys {
+            let number_pool_item = &other_number_pool.number_pool_items[key];
             if number_pool_item.missing > 0 {
                 if number_pool_item.pool.len() == number_pool_item.needs {
-                    numbers.extend(number_pool_item.pool.difference(current_data.selected_numbers_set()));
+                    let mut difference = number_pool_item.pool.difference(current_data.selected_numbers_set());
+                    difference.sort_unstable();
+                    numbers.extend(difference);
                 } else {
                     numbers.push(number_pool_item
                         .pool
-                        .random_number()
+                        .random_number_with_rng(current_data.rng())
                     );
                 }
             }
@@ -413,6 +457,23 @@ impl RuleTrait for NumberPool {
         }
         return Err(format!("{} count: {} is greater than count: {} ", self.name(), needs_count, count));
     }
+
+    fn prune_domain(
+        &self,
+        current_data: &CurrentData,
+        domain: &mut Vec<usize>,
+    ) {
+        let other_number_pool =
+            NumberPool::from_numbers_2(&self.number_pool_items, current_data.selected_numbers_set());
+        //A number that is already selected does not change "has"
+        domain.retain(|x| current_data.selected_numbers_set().contains(x) || other_number_pool.number_pool_items
+            .values()
+            .all(|item| !item.pool.contains(*x) || item.has < item.needs));
+    }
+
+    fn is_order_independent(&self) -> bool {
+        return true;
+    }
 }
 
 impl ExcludeRuleTrait for NumberPool {
@@ -437,4 +498,8 @@ impl ExcludeRuleTrait for NumberPool {
     fn exclude_name(&self) -> String {
         return self.name();
     }
+
+    fn is_order_independent(&self) -> bool {
+        return true;
+    }
 }
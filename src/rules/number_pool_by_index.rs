@@ -30,6 +30,18 @@ impl NumberPoolItemByIndex {
             key: key.to_owned(),
         };
     }
+
+    pub fn pool(&self) -> &PoolType {
+        return &self.pool;
+    }
+
+    pub fn indexes(&self) -> &HashSet<usize> {
+        return &self.indexes;
+    }
+
+    pub fn key(&self) -> &str {
+        return &self.key;
+    }
 }
 
 impl NumberPoolByIndex {
@@ -38,6 +50,10 @@ impl NumberPoolByIndex {
             number_pool_items: number_pool_items.to_vec(),
         };
     }
+
+    pub fn number_pool_items(&self) -> &Vec<NumberPoolItemByIndex> {
+        return &self.number_pool_items;
+    }
 }
 
 impl Display for NumberPoolByIndex {
@@ -70,7 +86,7 @@ impl RuleTrait for NumberPoolByIndex {
     ) -> std::result::Result<Vec<usize>, String> {
         for number_pool_item in &self.number_pool_items {
             if number_pool_item.indexes.contains(&current_data.selected_numbers().len()) {
-                let number = number_pool_item.pool.random_number();
+                let number = number_pool_item.pool.random_number_with_rng(current_data.rng());
                 return Ok(vec![number]);
             }
         }
@@ -119,6 +135,19 @@ impl RuleTrait for NumberPoolByIndex {
     ) -> std::result::Result<bool, String> {
         return Ok(true);
     }
+
+    fn prune_domain(
+        &self,
+        current_data: &CurrentData,
+        domain: &mut Vec<usize>,
+    ) {
+        let idx = current_data.selected_numbers().len();
+        for number_pool_item in &self.number_pool_items {
+            if number_pool_item.indexes.contains(&idx) {
+                domain.retain(|x| number_pool_item.pool.contains(*x));
+            }
+        }
+    }
 }
 
 impl ExcludeRuleTrait for NumberPoolByIndex {
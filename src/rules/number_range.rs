@@ -53,6 +53,12 @@ impl NumberRange {
         };
     }
 
+    //Range of the number at idx, None when the number has no range
+    pub fn range(&self, idx: usize) -> Option<(usize, usize)> {
+        let key = if self.use_0_idx_for_all {0} else {idx};
+        return self.ranges.get(&key).copied();
+    }
+
     pub fn len(&self) -> usize {
         return self.ranges.len();
     }
@@ -137,6 +143,31 @@ impl RuleTrait for NumberRange {
     ) -> std::result::Result<bool, String> {
         return Ok(true);
     }
+
+    fn prune_domain(
+        &self,
+        current_data: &CurrentData,
+        domain: &mut Vec<usize>,
+    ) {
+        let key = if self.use_0_idx_for_all {0} else {current_data.selected_numbers().len()};
+        if let Some((min, max)) = self.ranges.get(&key).copied() {
+            domain.retain(|x| *x >= min && *x <= max);
+        }
+    }
+
+    fn is_order_independent(&self) -> bool {
+        return self.use_0_idx_for_all;
+    }
+
+    fn violating_positions(
+        &self,
+        current_data: &CurrentData,
+    ) -> Option<Vec<usize>> {
+        return Some(current_data.selected_numbers().iter().enumerate()
+            .filter(|(idx, x)| self.range(*idx).is_some_and(|(min, max)| **x < min || **x > max))
+            .map(|(idx, _)| idx)
+            .collect());
+    }
 }
 
 impl ExcludeRuleTrait for NumberRange {
@@ -164,4 +195,8 @@ impl ExcludeRuleTrait for NumberRange {
     fn exclude_name(&self) -> String {
         return self.name();
     }
+
+    fn is_order_independent(&self) -> bool {
+        return self.use_0_idx_for_all;
+    }
 }
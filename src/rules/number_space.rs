@@ -1,6 +1,6 @@
 
 use crate::random::CurrentData;
-use crate::random_trait::get_random_trait;
+use crate::random_trait::{get_random_trait, RandomTrait};
 use crate::rules::{MapAnyValue, RuleTrait, IsWithinErrorType};
 use crate::settings::Settings;
 use std::any::Any;
@@ -43,25 +43,29 @@ impl NumberSpaceType {
     }
 
     pub fn get_number(&self, number_space_base: usize, max: usize) -> usize {
+        return self.get_number_with_rng(number_space_base, max, get_random_trait().as_ref());
+    }
+
+    pub fn get_number_with_rng(&self, number_space_base: usize, max: usize, rng: &dyn RandomTrait) -> usize {
         return match *self {//TODO check dereference 7;3 between(1-3)
-            NumberSpaceType::Lt(v) => get_random_trait().get_number(number_space_base + 1, number_space_base + v - 1),     //7+1=8;7+3-1=9 -- 8,9
-            NumberSpaceType::Lte(v) => get_random_trait().get_number(number_space_base + 1, number_space_base + v),        //7+1=8;7+3=10  -- 8,9,10
+            NumberSpaceType::Lt(v) => rng.get_number(number_space_base + 1, number_space_base + v - 1),     //7+1=8;7+3-1=9 -- 8,9
+            NumberSpaceType::Lte(v) => rng.get_number(number_space_base + 1, number_space_base + v),        //7+1=8;7+3=10  -- 8,9,10
             NumberSpaceType::Eq(v) => number_space_base + v,                                                                        //7+3=10        -- 10
             NumberSpaceType::Gt(v) => {
                 if (number_space_base + v + 1) <= max { 
-                    get_random_trait().get_number(number_space_base + v + 1, max)
+                    rng.get_number(number_space_base + v + 1, max)
                 } else {
                     0
                 }
             },                                                                                                                                                                                                                                                 
             NumberSpaceType::Gte(v) => {
                 if (number_space_base + v) <= max { 
-                    get_random_trait().get_number(number_space_base + v, max)
+                    rng.get_number(number_space_base + v, max)
                 } else {
                     0
                 }
             },                               
-            NumberSpaceType::Between(lower_bound, upper_bound) => get_random_trait().get_number(number_space_base + lower_bound, number_space_base + upper_bound), //7+1=8;7+3=10  -- 8,9,10
+            NumberSpaceType::Between(lower_bound, upper_bound) => rng.get_number(number_space_base + lower_bound, number_space_base + upper_bound), //7+1=8;7+3=10  -- 8,9,10
         };
     }
 }
@@ -286,13 +290,14 @@ impl RuleTrait for NumberSpace {
                     *current_data.selected_numbers_sorted().last().unwrap()
                 } else {
                     let (min, max) = Settings::get_min_max("NumberRange", current_data.shared_data());
-                    let val = get_random_trait().get_number(min, max);
+                    let val = current_data.rng().get_number(min, max);
                     numbers.push(val);
                     val
                 };
-                numbers.push(number_space_item.number_space_type.get_number(
+                numbers.push(number_space_item.number_space_type.get_number_with_rng(
                         number_space_base,
-                        Settings::get_min_max("NumberRange", current_data.shared_data()).1
+                        Settings::get_min_max("NumberRange", current_data.shared_data()).1,
+                        current_data.rng()
                     )
                 );
             }
@@ -365,6 +370,32 @@ impl RuleTrait for NumberSpace {
     ) -> std::result::Result<bool, String> {
         return Ok(true);
     }
+
+    fn is_order_independent(&self) -> bool {
+        return true;
+    }
+
+    //The spaces between sorted numbers can not change once the next number is picked
+    fn prune_sorted_domain(
+        &self,
+        current_data: &CurrentData,
+        domain: &mut Vec<usize>,
+    ) {
+        let last = match current_data.selected_numbers().last() {
+            Some(v) => *v,
+            None => return,
+        };
+        let num_spaces = NumberSpaceItem::get_num_spaces(current_data.selected_numbers(), true);
+        let has: Vec<usize> = self.number_space_items.iter().map(|x| x.number_space_type.has(&num_spaces)).collect();
+        let num_spaces_left = current_data.settings().count().saturating_sub(current_data.selected_numbers().len() + 1);
+        domain.retain(|x| {
+            let num_space = x.saturating_sub(last);
+            return self.number_space_items.iter().zip(&has).all(|(number_space_item, has)| {
+                let has = has + usize::from(number_space_item.number_space_type.is_match(num_space));
+                return has <= number_space_item.needs && number_space_item.needs - has <= num_spaces_left;
+            });
+        });
+    }
 }
 
 impl ExcludeRuleTrait for NumberSpace {
@@ -389,4 +420,8 @@ impl ExcludeRuleTrait for NumberSpace {
     fn exclude_name(&self) -> String {
         return self.name();
     }
+
+    fn is_order_independent(&self) -> bool {
+        return true;
+    }
 }
@@ -1,5 +1,5 @@
 use crate::random::CurrentData;
-use crate::random_trait::{get_random_trait, get_random_vec_item};
+use crate::random_trait::{get_random_trait, get_random_vec_item_with_rng, RandomTrait};
 use crate::rules::{MapAnyValue, RuleTrait, IsWithinErrorType};
 use crate::settings::Settings;
 use std::any::Any;
@@ -54,8 +54,11 @@ impl OddEven {
     }
 
     pub fn even_number(min: usize, max: usize) -> usize {
+        return OddEven::even_number_with_rng(min, max, get_random_trait().as_ref());
+    }
+    pub fn even_number_with_rng(min: usize, max: usize, rng: &dyn RandomTrait) -> usize {
         //for _ in 0..20 {
-        let number = get_random_trait().get_number(min, max);
+        let number = rng.get_number(min, max);
         if OddEven::is_even(number) {
             return number;
         } else {
@@ -69,8 +72,11 @@ impl OddEven {
         //panic!("Could not find an even number in range: {}-{}", min, max);
     }
     pub fn odd_number(min: usize, max: usize) -> usize {
+        return OddEven::odd_number_with_rng(min, max, get_random_trait().as_ref());
+    }
+    pub fn odd_number_with_rng(min: usize, max: usize, rng: &dyn RandomTrait) -> usize {
         //for _ in 0..20 {
-        let number = get_random_trait().get_number(min, max);
+        let number = rng.get_number(min, max);
         if OddEven::is_odd(number) {
             return number;
         } else {
@@ -149,12 +155,12 @@ impl RuleTrait for OddEven {
         }
         if !pool_keys.is_empty() {
             let (min, max) = Settings::get_min_max("NumberRange", current_data.shared_data());
-            let selected_pool_key = get_random_vec_item(&pool_keys);
+            let selected_pool_key = get_random_vec_item_with_rng(&pool_keys, current_data.rng());
             let mut number = 0_usize;
             if *selected_pool_key == OddEvenKey::Odd {
-                number = OddEven::odd_number(min, max);
+                number = OddEven::odd_number_with_rng(min, max, current_data.rng());
             } else if *selected_pool_key == OddEvenKey::Even {
-                number = OddEven::even_number(min, max);
+                number = OddEven::even_number_with_rng(min, max, current_data.rng());
             }
             return Ok(vec![number]);
         }
@@ -202,6 +208,37 @@ impl RuleTrait for OddEven {
         }
         return Err(format!("Odd count: {} and Even count: {} is greater than count: {} ", self.odd, self.even, count));
     }
+
+    fn prune_domain(
+        &self,
+        current_data: &CurrentData,
+        domain: &mut Vec<usize>,
+    ) {
+        let other = OddEven::from_numbers(current_data.selected_numbers());
+        domain.retain(|x| if OddEven::is_odd(*x) { other.odd < self.odd } else { other.even < self.even });
+    }
+
+    fn is_order_independent(&self) -> bool {
+        return true;
+    }
+
+    //The first of the extra odd or even numbers, so the numbers picked before make room
+    fn violating_positions(
+        &self,
+        current_data: &CurrentData,
+    ) -> Option<Vec<usize>> {
+        let other = OddEven::from_numbers(current_data.selected_numbers());
+        let (is_even, extra) = if other.odd > self.odd {
+            (false, other.odd - self.odd)
+        } else {
+            (true, other.even.saturating_sub(self.even))
+        };
+        return Some(current_data.selected_numbers().iter().enumerate()
+            .filter(|(_, x)| OddEven::is_even(**x) == is_even)
+            .map(|(idx, _)| idx)
+            .take(extra)
+            .collect());
+    }
 }
 
 impl ExcludeRuleTrait for OddEven {
@@ -226,4 +263,8 @@ impl ExcludeRuleTrait for OddEven {
     fn exclude_name(&self) -> String {
         return self.name();
     }
+
+    fn is_order_independent(&self) -> bool {
+        return true;
+    }
 }
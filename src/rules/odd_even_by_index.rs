@@ -41,6 +41,10 @@ impl OddEvenByIndex {
             odd_even
         };
     }
+
+    pub fn odd_even(&self) -> &HashMap<usize, OddEvenKey> {
+        return &self.odd_even;
+    }
 }
 
 impl Display for OddEvenByIndex {
@@ -79,9 +83,9 @@ impl RuleTrait for OddEvenByIndex {
             let (min, max) = Settings::get_min_max("NumberRange", current_data.shared_data());
             
             if self.odd_even[&current_data.selected_numbers().len()] == OddEvenKey::Odd {
-                return Ok(vec![OddEven::odd_number(min, max)]);
+                return Ok(vec![OddEven::odd_number_with_rng(min, max, current_data.rng())]);
             } else if self.odd_even[&current_data.selected_numbers().len()] == OddEvenKey::Even {
-                return Ok(vec![OddEven::even_number(min, max)]);
+                return Ok(vec![OddEven::even_number_with_rng(min, max, current_data.rng())]);
             } else {
                 return Err(String::from("Skip"));
             }
@@ -133,6 +137,18 @@ impl RuleTrait for OddEvenByIndex {
     ) -> std::result::Result<bool, String> {
         return Ok(true);
     }
+
+    fn prune_domain(
+        &self,
+        current_data: &CurrentData,
+        domain: &mut Vec<usize>,
+    ) {
+        match self.odd_even.get(&current_data.selected_numbers().len()) {
+            Some(OddEvenKey::Odd) => domain.retain(|x| OddEven::is_odd(*x)),
+            Some(OddEvenKey::Even) => domain.retain(|x| OddEven::is_even(*x)),
+            None => {},
+        }
+    }
 }
 
 impl ExcludeRuleTrait for OddEvenByIndex {
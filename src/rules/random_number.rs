@@ -1,5 +1,4 @@
 use crate::random::CurrentData;
-use crate::random_trait::get_random_trait;
 use crate::rules::{MapAnyValue, RuleTrait, IsWithinErrorType};
 use crate::settings::Settings;
 use std::any::Any;
@@ -57,7 +56,7 @@ impl RuleTrait for RandomNumber {
         current_data: &CurrentData,
     ) -> std::result::Result<Vec<usize>, String> {
         let (min, max) = Settings::get_min_max("NumberRange", current_data.shared_data());
-        return Ok(vec![get_random_trait().get_number(min, max)]);
+        return Ok(vec![current_data.rng().get_number(min, max)]);
     }
 
     fn is_within_range(
@@ -84,4 +83,8 @@ impl RuleTrait for RandomNumber {
     ) -> std::result::Result<bool, String> {
         return Ok(true);
     }
+
+    fn is_order_independent(&self) -> bool {
+        return true;
+    }
 }
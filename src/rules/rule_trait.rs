@@ -15,7 +15,20 @@ pub enum IsWithinErrorType {
     MakePriority,
 }
 
-pub trait RuleTrait: RuleTraitClone + Display + std::fmt::Debug {
+//Rules have to be Send + Sync with the "parallel" feature so Settings can be shared between threads
+#[cfg(feature="parallel")]
+pub trait MaybeSendSync: Send + Sync {}
+
+#[cfg(feature="parallel")]
+impl<T: Send + Sync> MaybeSendSync for T {}
+
+#[cfg(not(feature="parallel"))]
+pub trait MaybeSendSync {}
+
+#[cfg(not(feature="parallel"))]
+impl<T> MaybeSendSync for T {}
+
+pub trait RuleTrait: RuleTraitClone + Display + std::fmt::Debug + MaybeSendSync {
     fn is_within_range(
         &self,
         current_data: &CurrentData,
@@ -44,6 +57,42 @@ pub trait RuleTrait: RuleTraitClone + Display + std::fmt::Debug {
     ) -> std::result::Result<bool, String>;
 
     fn name(&self) -> String;
+
+    //Used by the backtracking solver.  Removes the numbers from domain that can never be the next
+    //number (index selected_numbers().len()) of a match.  Only remove numbers that can never match,
+    //otherwise the solver can miss matches.
+    fn prune_domain(
+        &self,
+        _current_data: &CurrentData,
+        _domain: &mut Vec<usize>,
+    ) {
+    }
+
+    //Used by the backtracking solver instead of prune_domain when every rule is order independent.
+    //Then selected_numbers() are sorted and every number of domain is at least the last one, so
+    //the next numbers can never go before them.  Only remove numbers that can never match.
+    fn prune_sorted_domain(
+        &self,
+        current_data: &CurrentData,
+        domain: &mut Vec<usize>,
+    ) {
+        self.prune_domain(current_data, domain);
+    }
+
+    //True when the order of the numbers does not change is_match
+    fn is_order_independent(&self) -> bool {
+        return false;
+    }
+
+    //Positions in selected_numbers() of the numbers that make is_within_range or is_match fail.
+    //When the same error happens too many times only these numbers are removed instead of all of
+    //them.  None when the rule can not tell, then all numbers are removed.
+    fn violating_positions(
+        &self,
+        _current_data: &CurrentData,
+    ) -> Option<Vec<usize>> {
+        return None;
+    }
 }
 
 pub trait RuleTraitClone {
@@ -1,5 +1,5 @@
 use crate::random::CurrentData;
-use crate::random_trait::get_random_vec_item;
+use crate::random_trait::{get_random_trait, get_random_vec_item_with_rng, RandomTrait};
 use crate::rules::{MapAnyValue, RuleTrait, IsWithinErrorType};
 use crate::settings::Settings;
 use std::any::Any;
@@ -64,6 +64,10 @@ impl Sequential {
     }
 
     pub fn needs_seq(&self, other: &Self, settings: &Settings) -> usize {
+        return self.needs_seq_with_rng(other, settings, get_random_trait().as_ref());
+    }
+
+    pub fn needs_seq_with_rng(&self, other: &Self, settings: &Settings, rng: &dyn RandomTrait) -> usize {
         if other.not == settings.count() {
             return 0;
         }
@@ -74,10 +78,40 @@ impl Sequential {
             }
         }
         if !other_seq_counts.is_empty() {
-            return *get_random_vec_item(&other_seq_counts);
+            return *get_random_vec_item_with_rng(&other_seq_counts, rng);
         }
         return 0;
     }
+
+    //False when sorted_numbers with number after them can never be part of a match.  Runs before
+    //the last one can not change anymore because the next numbers are at least number, the last
+    //run can only get longer.
+    fn is_possible_with(&self, sorted_numbers: &[usize], number: usize) -> bool {
+        if sorted_numbers.is_empty() {
+            return true;
+        }
+        let mut seq_counts: Vec<usize> = self.seq_counts.clone();
+        let mut not: usize = 0;
+        let mut run: usize = 1;
+        for (idx, next) in sorted_numbers.iter().skip(1).chain(std::iter::once(&number)).enumerate() {
+            if sorted_numbers[idx] + 1 == *next {
+                run += 1;
+                continue;
+            }
+            if run == 1 {
+                not += 1;
+            } else {
+                match seq_counts.iter().position(|x| *x == run) {
+                    Some(pos) => {
+                        seq_counts.swap_remove(pos);
+                    },
+                    None => return false,
+                }
+            }
+            run = 1;
+        }
+        return not <= self.not && (run == 1 || seq_counts.iter().any(|x| *x >= run));
+    }
 }
 
 impl Display for Sequential {
@@ -121,13 +155,13 @@ impl RuleTrait for Sequential {
         current_data: &CurrentData
     ) -> std::result::Result<Vec<usize>, String> {
         let act_seq = Sequential::from_numbers(current_data, false);
-        let seq_count_needed = act_seq.needs_seq(self, current_data.settings());
+        let seq_count_needed = act_seq.needs_seq_with_rng(self, current_data.settings(), current_data.rng());
         if seq_count_needed > 0 {
             let range = if current_data.selected_numbers().is_empty() {
                 let num = current_data.settings().get_number_within_number_range(current_data).unwrap()[0];
                 num..=(num + seq_count_needed - 1)
             } else {
-                let num = *get_random_vec_item(&current_data.selected_numbers());
+                let num = *get_random_vec_item_with_rng(current_data.selected_numbers(), current_data.rng());
                 (num + 1)..=(num + seq_count_needed - 1)
             };
             let mut seq_digits: Vec<usize> = Vec::new();
@@ -194,6 +228,18 @@ impl RuleTrait for Sequential {
         }
         return Err(format!("{} count: {} is greater than count: {} ", self.name(), this_count, count));
     }
+
+    fn is_order_independent(&self) -> bool {
+        return true;
+    }
+
+    fn prune_sorted_domain(
+        &self,
+        current_data: &CurrentData,
+        domain: &mut Vec<usize>,
+    ) {
+        domain.retain(|x| self.is_possible_with(current_data.selected_numbers(), *x));
+    }
 }
 
 impl ExcludeRuleTrait for Sequential {
@@ -218,4 +264,8 @@ impl ExcludeRuleTrait for Sequential {
     fn exclude_name(&self) -> String {
         return self.name();
     }
+
+    fn is_order_independent(&self) -> bool {
+        return true;
+    }
 }
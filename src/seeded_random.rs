@@ -0,0 +1,35 @@
+use std::sync::atomic::{AtomicU64, Ordering};
+use crate::random_trait::RandomTrait;
+
+const GOLDEN_GAMMA: u64 = 0x9E37_79B9_7F4A_7C15;
+
+//SplitMix64, the same seed always produces the same draws
+#[derive(Debug)]
+pub struct SeededRandom {
+    state: AtomicU64,
+}
+
+impl SeededRandom {
+    pub fn new(seed: u64) -> SeededRandom {
+        return SeededRandom { state: AtomicU64::new(seed) };
+    }
+}
+
+impl Clone for SeededRandom {
+    fn clone(&self) -> SeededRandom {
+        return SeededRandom { state: AtomicU64::new(self.state.load(Ordering::Relaxed)) };
+    }
+}
+
+impl RandomTrait for SeededRandom {
+    fn next_u64(&self) -> u64 {
+        let mut z = self.state.fetch_add(GOLDEN_GAMMA, Ordering::Relaxed).wrapping_add(GOLDEN_GAMMA);
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        return z ^ (z >> 31);
+    }
+
+    fn seed(&self) -> Option<u64> {
+        return Some(self.state.load(Ordering::Relaxed));
+    }
+}
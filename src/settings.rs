@@ -1,6 +1,51 @@
+use crate::analyze::infeasible_reasons;
+use crate::cancellation::CancellationToken;
+use crate::entropy::{entropy_report, EntropyReport};
+use crate::generator::Generator;
+use crate::observer::GenerationObserver;
 use crate::random::CurrentData;
+use crate::random_error::{RandomError, RuleViolation};
+use crate::random_trait::{get_random_trait, RandomTrait};
 use crate::rules::{MapAnyValue, RuleTrait, RandomNumber, ExcludeRuleTrait};
+use crate::seeded_random::SeededRandom;
+use crate::solutions::{count_solutions, count_solutions_with_rng, SolutionCount};
+use once_cell::sync::OnceCell;
 use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+
+#[derive(Debug, PartialEq, Eq, Hash, Copy, Clone)]
+pub enum SolverType {
+    //Picks numbers from the rules, checks them and starts over after too many errors
+    Incremental,
+    //Searches every combination the NumberRange allows, finds a match when one exists
+    Backtracking,
+    //Every match has the same chance to be picked, needs a NumberRange with a max or pools that hold
+    //every number of every index, see solutions::count_solutions_with_rng
+    Uniform,
+}
+
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Copy, Clone)]
+pub enum LogLevel {
+    //No logs
+    Off,
+    //Only the error that stopped numbers from being created or the entropy report that could not be
+    //made for min_entropy_bits, nothing is logged while numbers are created so this keeps creating
+    //numbers free of log allocations
+    Error,
+    //Attempts, clears and every rule that failed
+    Info,
+    //Every number that was tried and the rule that turned it down, with the events in
+    //RandomResult::trace
+    Trace,
+}
+
+impl LogLevel {
+    //True when logs of level are kept with this log level
+    pub fn includes(&self, level: LogLevel) -> bool {
+        return level != LogLevel::Off && level <= *self;
+    }
+}
 
 #[derive(Clone)]
 pub struct Settings {
@@ -9,7 +54,24 @@ pub struct Settings {
     exclude_rules: Option<Vec<Box<dyn ExcludeRuleTrait>>>,
     max_tries: usize,
     max_is_match_attempts: usize,
-    max_specific_error_count: usize
+    max_duplicate_retries: usize,
+    max_specific_error_count: usize,
+    bad_request_errors: Vec<String>,
+    infeasible_reasons: Vec<String>,
+    solver: SolverType,
+    max_solver_nodes: Option<usize>,
+    min_entropy_bits: Option<f64>,
+    log_level: LogLevel,
+    max_logs: usize,
+    time_rules: bool,
+    record_draws: bool,
+    timeout: Option<Duration>,
+    cancellation_token: Option<CancellationToken>,
+    observer: Option<Arc<dyn GenerationObserver>>,
+    //Numbers that stay at their index, set by random::complete_numbers
+    fixed_numbers: Vec<Option<usize>>,
+    //Count of the entropy report, counting can take long so it is only done once
+    solution_count: OnceCell<Result<SolutionCount, RandomError>>,
 }
 
 impl Settings {
@@ -20,6 +82,29 @@ impl Settings {
         return Settings::with_exclude_rules(expected_rules, count, None)
     }
 
+    pub fn builder(count: usize) -> SettingsBuilder {
+        return SettingsBuilder::new(count);
+    }
+
+    pub fn try_new(
+        expected_rules: &[Box<dyn RuleTrait>],
+        count: usize,
+    ) -> Result<Settings, RandomError> {
+        return Settings::try_with_exclude_rules(expected_rules, count, None)
+    }
+
+    pub fn try_with_exclude_rules(
+        expected_rules: &[Box<dyn RuleTrait>],
+        count: usize,
+        exclude_rules: Option<Vec<Box<dyn ExcludeRuleTrait>>>
+    ) -> Result<Settings, RandomError> {
+        let settings = Settings::with_exclude_rules(expected_rules, count, exclude_rules);
+        if !settings.bad_request_errors.is_empty() {
+            return Err(RandomError::BadRequest { errors: settings.bad_request_errors });
+        }
+        return Ok(settings);
+    }
+
     pub fn with_exclude_rules(
         expected_rules: &[Box<dyn RuleTrait>],
         count: usize,
@@ -29,21 +114,102 @@ impl Settings {
         if expected_rules_clone.iter().all(|x| x.name() != "RandomNumber") {
             expected_rules_clone.push(Box::new(RandomNumber{}));
         }
+        let mut bad_request_errors: Vec<String> = Vec::new();
         for rule in &expected_rules_clone {
             if let Some(e) = rule.check_count(count).err() {
-                panic!("{}", e);
+                bad_request_errors.push(e);
             }
         }
+        let infeasible_reasons = infeasible_reasons(&expected_rules_clone, count);
         return Settings {
             expected_rules: expected_rules_clone,
             count,
             exclude_rules,
             max_tries: 500,
             max_is_match_attempts: 15,
-            max_specific_error_count: 4 * count
+            max_duplicate_retries: 15,
+            max_specific_error_count: 4 * count,
+            bad_request_errors,
+            infeasible_reasons,
+            solver: SolverType::Incremental,
+            max_solver_nodes: None,
+            min_entropy_bits: None,
+            log_level: LogLevel::Error,
+            max_logs: 1000,
+            time_rules: false,
+            record_draws: false,
+            timeout: None,
+            cancellation_token: None,
+            observer: None,
+            fixed_numbers: Vec::new(),
+            solution_count: OnceCell::new(),
         };
     }
 
+    //Checks the rules for combinations that can never match without generating any numbers.
+    //Err explains every reason the rules can never match.
+    pub fn analyze(&self) -> Result<(), RandomError> {
+        if self.infeasible_reasons.is_empty() {
+            return Ok(());
+        }
+        return Err(RandomError::Infeasible { reason: self.infeasible_reasons.join("; ") });
+    }
+
+    //Checks numbers made somewhere else, like a password a user picked.  Err has every rule the
+    //numbers do not match, not just the first one.
+    pub fn validate(&self, numbers: &[usize]) -> Result<(), RandomError> {
+        if !self.bad_request_errors.is_empty() {
+            return Err(RandomError::BadRequest { errors: self.bad_request_errors.clone() });
+        }
+        let mut violations: Vec<RuleViolation> = Vec::new();
+        if numbers.len() != self.count {
+            violations.push(RuleViolation::new("Count", &format!("Expected {} numbers.  Actual {} numbers.", self.count, numbers.len())));
+        }
+        let numbers = numbers.to_vec();
+        let shared_data: HashMap<String, HashMap<String, MapAnyValue>> = HashMap::new();
+        let current_data = CurrentData::new(&numbers, self, &shared_data);
+        for expected_rule in &self.expected_rules {
+            if let Err(e) = expected_rule.is_match(&current_data) {
+                violations.push(RuleViolation::new(&expected_rule.name(), &e));
+            }
+        }
+        for exclude_rule in self.exclude_rules.iter().flatten() {
+            if let Err(e) = exclude_rule.is_excluded(&current_data) {
+                violations.push(RuleViolation::new(&format!("exr-{}", exclude_rule.exclude_name()), &e));
+            }
+        }
+        if !violations.is_empty() {
+            return Err(RandomError::RuleViolations { violations });
+        }
+        return Ok(());
+    }
+
+    //Checks the char codes of value, see validate
+    pub fn validate_str(&self, value: &str) -> Result<(), RandomError> {
+        return self.validate(&value.chars().map(|x| x as usize).collect::<Vec<usize>>());
+    }
+
+    //Number of different number vectors that match the rules, see solutions::count_solutions_with_rng
+    pub fn count_solutions(&self) -> Result<SolutionCount, RandomError> {
+        return count_solutions(self);
+    }
+
+    //Entropy of the numbers created with the default rng
+    pub fn entropy(&self) -> Result<EntropyReport, RandomError> {
+        return self.entropy_with_rng(get_random_trait().as_ref());
+    }
+
+    //Entropy of the numbers created with rng.  Estimated counts use their own seeded rng, so
+    //rng is never used and the report is the same every time.
+    pub fn entropy_with_rng(&self, rng: &dyn RandomTrait) -> Result<EntropyReport, RandomError> {
+        let solution_count = self.solution_count.get_or_init(|| count_solutions_with_rng(self, &SeededRandom::new(0))).clone()?;
+        return entropy_report(self, solution_count, rng);
+    }
+
+    pub fn iter(&self) -> Generator<'_> {
+        return Generator::new(self);
+    }
+
     pub fn get_min_max(
         key: &str,
         shared_data: &HashMap<String, HashMap<String, MapAnyValue>>,
@@ -86,7 +252,315 @@ impl Settings {
         return self.max_is_match_attempts;
     }
 
+    pub fn max_duplicate_retries(&self) -> usize {
+        return self.max_duplicate_retries;
+    }
+
     pub fn max_specific_error_count(&self) -> usize {
         return self.max_specific_error_count;
     }
+
+    pub fn bad_request_errors(&self) -> &Vec<String> {
+        return &self.bad_request_errors;
+    }
+
+    pub fn solver(&self) -> SolverType {
+        return self.solver;
+    }
+
+    pub fn max_solver_nodes(&self) -> Option<usize> {
+        return self.max_solver_nodes;
+    }
+
+    pub fn min_entropy_bits(&self) -> Option<f64> {
+        return self.min_entropy_bits;
+    }
+
+    pub fn log_level(&self) -> LogLevel {
+        return self.log_level;
+    }
+
+    pub fn max_logs(&self) -> usize {
+        return self.max_logs;
+    }
+
+    pub fn time_rules(&self) -> bool {
+        return self.time_rules;
+    }
+
+    pub fn record_draws(&self) -> bool {
+        return self.record_draws;
+    }
+
+    pub fn timeout(&self) -> Option<Duration> {
+        return self.timeout;
+    }
+
+    pub fn cancellation_token(&self) -> &Option<CancellationToken> {
+        return &self.cancellation_token;
+    }
+
+    pub fn observer(&self) -> Option<&dyn GenerationObserver> {
+        return self.observer.as_deref();
+    }
+
+    pub(crate) fn fixed_numbers(&self) -> &Vec<Option<usize>> {
+        return &self.fixed_numbers;
+    }
+
+    //Settings that keep fixed_numbers at their index, only the incremental solver can keep them
+    pub(crate) fn with_fixed_numbers(&self, fixed_numbers: &[Option<usize>]) -> Settings {
+        let mut settings = self.clone();
+        settings.fixed_numbers = fixed_numbers.to_vec();
+        if fixed_numbers.len() > settings.count {
+            settings.bad_request_errors.push(format!(
+                "fixed_numbers: {} must not be more than count: {}", fixed_numbers.len(), settings.count
+            ));
+        }
+        if settings.solver != SolverType::Incremental && fixed_numbers.iter().any(Option::is_some) {
+            settings.bad_request_errors.push(format!("{:?} solver can not complete fixed_numbers", settings.solver));
+        }
+        return settings;
+    }
+
+    //Settings that keep numbers except the ones at indexes
+    pub(crate) fn with_rerolled_numbers(&self, numbers: &[usize], indexes: &[usize]) -> Settings {
+        let mut fixed_numbers: Vec<Option<usize>> = numbers.iter().copied().map(Some).collect();
+        let mut bad_indexes: Vec<usize> = Vec::new();
+        for idx in indexes {
+            match fixed_numbers.get_mut(*idx) {
+                Some(v) => *v = None,
+                None => bad_indexes.push(*idx),
+            }
+        }
+        let mut settings = self.with_fixed_numbers(&fixed_numbers);
+        if numbers.len() != settings.count {
+            settings.bad_request_errors.push(format!("numbers: {} must be count: {} numbers", numbers.len(), settings.count));
+        }
+        if !bad_indexes.is_empty() {
+            settings.bad_request_errors.push(format!("indexes {:?} must be less than {}", bad_indexes, numbers.len()));
+        }
+        return settings;
+    }
+
+    //Fixed number at idx
+    pub(crate) fn fixed_number(&self, idx: usize) -> Option<usize> {
+        return self.fixed_numbers.get(idx).copied().flatten();
+    }
+
+    //True when the order of the numbers does not change any rule
+    pub(crate) fn is_order_independent(&self) -> bool {
+        return self.expected_rules.iter().all(|x| x.is_order_independent())
+            && self.exclude_rules.iter().flatten().all(|x| x.is_order_independent());
+    }
+}
+
+#[derive(Clone)]
+pub struct SettingsBuilder {
+    expected_rules: Vec<Box<dyn RuleTrait>>,
+    count: usize,
+    exclude_rules: Vec<Box<dyn ExcludeRuleTrait>>,
+    max_tries: usize,
+    max_is_match_attempts: usize,
+    max_duplicate_retries: usize,
+    max_specific_error_count: Option<usize>,
+    solver: SolverType,
+    max_solver_nodes: Option<usize>,
+    min_entropy_bits: Option<f64>,
+    log_level: LogLevel,
+    max_logs: usize,
+    time_rules: bool,
+    record_draws: bool,
+    timeout: Option<Duration>,
+    cancellation_token: Option<CancellationToken>,
+    observer: Option<Arc<dyn GenerationObserver>>,
+}
+
+impl SettingsBuilder {
+    pub fn new(count: usize) -> SettingsBuilder {
+        return SettingsBuilder {
+            expected_rules: Vec::new(),
+            count,
+            exclude_rules: Vec::new(),
+            max_tries: 500,
+            max_is_match_attempts: 15,
+            max_duplicate_retries: 15,
+            max_specific_error_count: None,
+            solver: SolverType::Incremental,
+            max_solver_nodes: None,
+            min_entropy_bits: None,
+            log_level: LogLevel::Error,
+            max_logs: 1000,
+            time_rules: false,
+            record_draws: false,
+            timeout: None,
+            cancellation_token: None,
+            observer: None,
+        };
+    }
+
+    pub fn rule(mut self, rule: Box<dyn RuleTrait>) -> SettingsBuilder {
+        self.expected_rules.push(rule);
+        return self;
+    }
+
+    pub fn rules(mut self, rules: &[Box<dyn RuleTrait>]) -> SettingsBuilder {
+        self.expected_rules.extend(rules.iter().cloned());
+        return self;
+    }
+
+    pub fn exclude_rule(mut self, exclude_rule: Box<dyn ExcludeRuleTrait>) -> SettingsBuilder {
+        self.exclude_rules.push(exclude_rule);
+        return self;
+    }
+
+    pub fn exclude_rules(mut self, exclude_rules: &[Box<dyn ExcludeRuleTrait>]) -> SettingsBuilder {
+        self.exclude_rules.extend(exclude_rules.iter().cloned());
+        return self;
+    }
+
+    pub fn count(mut self, count: usize) -> SettingsBuilder {
+        self.count = count;
+        return self;
+    }
+
+    pub fn max_tries(mut self, max_tries: usize) -> SettingsBuilder {
+        self.max_tries = max_tries;
+        return self;
+    }
+
+    pub fn max_is_match_attempts(mut self, max_is_match_attempts: usize) -> SettingsBuilder {
+        self.max_is_match_attempts = max_is_match_attempts;
+        return self;
+    }
+
+    //How many times a batch creates numbers again for a result that is the same as an earlier
+    //one before it counts it as missing, defaults to 15
+    pub fn max_duplicate_retries(mut self, max_duplicate_retries: usize) -> SettingsBuilder {
+        self.max_duplicate_retries = max_duplicate_retries;
+        return self;
+    }
+
+    //Defaults to 4 * count
+    pub fn max_specific_error_count(mut self, max_specific_error_count: usize) -> SettingsBuilder {
+        self.max_specific_error_count = Some(max_specific_error_count);
+        return self;
+    }
+
+    pub fn solver(mut self, solver: SolverType) -> SettingsBuilder {
+        self.solver = solver;
+        return self;
+    }
+
+    //Stops the backtracking solver with RandomResultType::Undecided after this many numbers were
+    //tried, defaults to no limit so the solver always finds numbers or proves there are none
+    pub fn max_solver_nodes(mut self, max_solver_nodes: usize) -> SettingsBuilder {
+        self.max_solver_nodes = Some(max_solver_nodes);
+        return self;
+    }
+
+    //Warns in the entropy report when the numbers have less entropy, every RandomResult gets the
+    //entropy report when it is set
+    pub fn min_entropy_bits(mut self, min_entropy_bits: f64) -> SettingsBuilder {
+        self.min_entropy_bits = Some(min_entropy_bits);
+        return self;
+    }
+
+    //Defaults to LogLevel::Error
+    pub fn log_level(mut self, log_level: LogLevel) -> SettingsBuilder {
+        self.log_level = log_level;
+        return self;
+    }
+
+    //Only the last max_logs logs are kept, defaults to 1000
+    pub fn max_logs(mut self, max_logs: usize) -> SettingsBuilder {
+        self.max_logs = max_logs;
+        return self;
+    }
+
+    //Measures the time spent in every rule for RandomResult::rule_stats, defaults to false
+    pub fn time_rules(mut self, time_rules: bool) -> SettingsBuilder {
+        self.time_rules = time_rules;
+        return self;
+    }
+
+    //Keeps every draw of the generator in RandomResult::replay_token, needed to replay a generator
+    //without a seed.  A seeded generator is replayed from its seed, defaults to false
+    pub fn record_draws(mut self, record_draws: bool) -> SettingsBuilder {
+        self.record_draws = record_draws;
+        return self;
+    }
+
+    //Every call that creates numbers stops with RandomResultType::TimedOut after this much time.
+    //The time is checked between attempts, so a slow rule can make it take longer.
+    pub fn timeout(mut self, timeout: Duration) -> SettingsBuilder {
+        self.timeout = Some(timeout);
+        return self;
+    }
+
+    //Creating numbers stops with RandomResultType::Cancelled once the token is cancelled, it is
+    //checked between attempts
+    pub fn cancellation_token(mut self, cancellation_token: CancellationToken) -> SettingsBuilder {
+        self.cancellation_token = Some(cancellation_token);
+        return self;
+    }
+
+    //Gets called while numbers are created, keep a clone of the Arc to read what it collected
+    pub fn observer(mut self, observer: Arc<dyn GenerationObserver>) -> SettingsBuilder {
+        self.observer = Some(observer);
+        return self;
+    }
+
+    pub fn build(&self) -> Result<Settings, RandomError> {
+        let mut settings = Settings::with_exclude_rules(
+            &self.expected_rules,
+            self.count,
+            if self.exclude_rules.is_empty() { None } else { Some(self.exclude_rules.clone()) }
+        );
+        settings.max_tries = self.max_tries;
+        settings.max_is_match_attempts = self.max_is_match_attempts;
+        settings.max_duplicate_retries = self.max_duplicate_retries;
+        if let Some(v) = self.max_specific_error_count {
+            settings.max_specific_error_count = v;
+        }
+        settings.solver = self.solver;
+        settings.max_solver_nodes = self.max_solver_nodes;
+        settings.min_entropy_bits = self.min_entropy_bits;
+        settings.log_level = self.log_level;
+        settings.max_logs = self.max_logs;
+        settings.time_rules = self.time_rules;
+        settings.record_draws = self.record_draws;
+        settings.timeout = self.timeout;
+        settings.cancellation_token.clone_from(&self.cancellation_token);
+        settings.observer.clone_from(&self.observer);
+
+        if settings.count == 0 {
+            settings.bad_request_errors.push("count must be greater than 0".to_owned());
+        }
+        if settings.max_tries == 0 {
+            settings.bad_request_errors.push("max_tries must be greater than 0".to_owned());
+        }
+        if settings.max_is_match_attempts == 0 || settings.max_is_match_attempts > settings.max_tries {
+            settings.bad_request_errors.push(format!(
+                "max_is_match_attempts: {} must be between 1 and max_tries: {}",
+                settings.max_is_match_attempts, settings.max_tries
+            ));
+        }
+        if settings.max_specific_error_count == 0 {
+            settings.bad_request_errors.push("max_specific_error_count must be greater than 0".to_owned());
+        }
+        if settings.max_solver_nodes == Some(0) {
+            settings.bad_request_errors.push("max_solver_nodes must be greater than 0".to_owned());
+        }
+        if settings.timeout.is_some_and(|x| x.is_zero()) {
+            settings.bad_request_errors.push("timeout must be greater than 0".to_owned());
+        }
+        if let Some(v) = settings.min_entropy_bits.filter(|x| !(*x >= 0.0 && x.is_finite())) {
+            settings.bad_request_errors.push(format!("min_entropy_bits: {} must be 0 or more", v));
+        }
+        if !settings.bad_request_errors.is_empty() {
+            return Err(RandomError::BadRequest { errors: settings.bad_request_errors });
+        }
+        return Ok(settings);
+    }
 }
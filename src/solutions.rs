@@ -0,0 +1,501 @@
+use crate::analyze::count_key;
+use crate::random::CurrentData;
+use crate::random_error::RandomError;
+use crate::random_trait::{get_random_trait, RandomTrait};
+use crate::rules::{
+    MapAnyValue, NoDuplicate, NumberPool, NumberPoolByIndex, NumberRange, OddEven, OddEvenByIndex, OddEvenKey, PoolType,
+    RandomNumber, RuleTrait,
+};
+use crate::settings::Settings;
+use crate::solver::Solver;
+use std::collections::{BTreeSet, HashMap};
+use std::fmt;
+use std::fmt::{Display, Formatter};
+
+pub(crate) const MAX_CLASS_STATES: usize = 200_000;
+const MAX_COUNT_NODES: usize = 500_000;
+const ESTIMATE_SAMPLES: usize = 20_000;
+
+#[derive(Debug, PartialEq, Copy, Clone)]
+pub enum SolutionCount {
+    //Exact number of matching numbers
+    Exact(u128),
+    //Exact number of matching numbers that is too large for u128, counted with f64
+    Approximate(f64),
+    //Estimated from how many random numbers matched the rules
+    Estimate { count: f64, samples: usize, matches: usize },
+}
+
+impl SolutionCount {
+    pub fn count(&self) -> f64 {
+        return match self {
+            SolutionCount::Exact(v) => *v as f64,
+            SolutionCount::Approximate(v) => *v,
+            SolutionCount::Estimate { count, .. } => *count,
+        };
+    }
+
+    pub fn is_exact(&self) -> bool {
+        return matches!(self, SolutionCount::Exact(_));
+    }
+
+    //Bits of entropy when one of the matches is picked uniformly
+    pub fn log2(&self) -> f64 {
+        return self.count().log2();
+    }
+}
+
+impl Display for SolutionCount {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self {
+            SolutionCount::Exact(v) => write!(f, "{}", v),
+            SolutionCount::Approximate(v) => write!(f, "~{:e}", v),
+            SolutionCount::Estimate { count, samples, matches } => {
+                write!(f, "~{:.0} (estimate, {} of {} samples matched)", count, matches, samples)
+            },
+        }
+    }
+}
+
+pub fn count_solutions(settings: &Settings) -> Result<SolutionCount, RandomError> {
+    return count_solutions_with_rng(settings, get_random_trait().as_ref());
+}
+
+//Counts every number vector that matches settings.  Built-in rules NumberRange, NoDuplicate,
+//OddEven, NumberPool, NumberPoolByIndex and OddEvenByIndex are counted exactly.  Other rules are
+//counted by searching every combination when there are few enough, otherwise the count is
+//estimated with random numbers from rng.  Every index needs a NumberRange, a NumberPoolByIndex or a
+//NumberPool whose needs add up to the count, otherwise the count is RandomError::Uncountable.
+pub fn count_solutions_with_rng(settings: &Settings, rng: &dyn RandomTrait) -> Result<SolutionCount, RandomError> {
+    if !settings.bad_request_errors().is_empty() {
+        return Err(RandomError::BadRequest { errors: settings.bad_request_errors().clone() });
+    }
+    if settings.analyze().is_err() {
+        return Ok(SolutionCount::Exact(0));
+    }
+    let ranges = index_ranges(settings)?;
+    if let Some(class_model) = ClassModel::new(settings, &ranges).filter(|x| x.is_exact()) {
+        match class_model.count::<u128>() {
+            Ok(v) => return Ok(SolutionCount::Exact(v)),
+            Err(CountStop::Overflow) => {
+                if let Ok(v) = class_model.count::<f64>() {
+                    return Ok(SolutionCount::Approximate(v));
+                }
+            },
+            Err(CountStop::TooManyStates) => {},
+        }
+    }
+    let expected_rules: Vec<&dyn RuleTrait> = settings.expected_rules().iter().map(|x| x.as_ref()).collect();
+    if let Some(v) = Solver::new(settings, &expected_rules, rng).count(MAX_COUNT_NODES) {
+        return Ok(SolutionCount::Exact(v));
+    }
+    return Ok(estimate(settings, &expected_rules, &ranges, rng));
+}
+
+//Range of the number at every index, from the NumberRange or else from the pools the number has to
+//be in
+pub(crate) fn index_ranges(settings: &Settings) -> Result<Vec<(usize, usize)>, RandomError> {
+    let number_range = settings.expected_rules().iter().find_map(|x| x.as_any().downcast_ref::<NumberRange>());
+    let number_pool_range = number_pool_range(settings);
+    let mut ranges: Vec<(usize, usize)> = Vec::with_capacity(settings.count());
+    for idx in 0..settings.count() {
+        let range = number_range.and_then(|x| x.range(idx))
+            .or_else(|| number_pool_by_index_range(settings, idx))
+            .or(number_pool_range);
+        match range {
+            Some(range) => ranges.push(range),
+            None => {
+                return Err(RandomError::Uncountable {
+                    reason: format!(
+                        "Every index needs a NumberRange, a NumberPoolByIndex or a NumberPool that holds every number to count the matching numbers, index {} has none",
+                        idx
+                    ),
+                });
+            },
+        }
+    }
+    return Ok(ranges);
+}
+
+//Smallest and largest number of the NumberPoolByIndex pools of idx, the number has to be in all of
+//them
+fn number_pool_by_index_range(settings: &Settings, idx: usize) -> Option<(usize, usize)> {
+    let mut range: Option<(usize, usize)> = None;
+    for expected_rule in settings.expected_rules() {
+        if let Some(number_pool_by_index) = expected_rule.as_any().downcast_ref::<NumberPoolByIndex>() {
+            for number_pool_item in number_pool_by_index.number_pool_items() {
+                if !number_pool_item.indexes().contains(&idx) {
+                    continue;
+                }
+                //An empty pool has no match, so any range holds every match
+                if let Some((min, max)) = number_pool_item.pool().bounds() {
+                    range = match range {
+                        Some((range_min, range_max)) if range_min.max(min) <= range_max.min(max) => {
+                            Some((range_min.max(min), range_max.min(max)))
+                        },
+                        Some(range) => Some(range),
+                        None => Some((min, max)),
+                    };
+                }
+            }
+        }
+    }
+    return range;
+}
+
+//Smallest and largest number of a NumberPool that holds every number, which is when its pools do
+//not share numbers and their needs add up to the count.  NumberPool counts different numbers, so a
+//number outside the pools would leave a pool short.
+fn number_pool_range(settings: &Settings) -> Option<(usize, usize)> {
+    for expected_rule in settings.expected_rules() {
+        if let Some(number_pool) = expected_rule.as_any().downcast_ref::<NumberPool>() {
+            let pools: Vec<&PoolType> = number_pool.number_pool_items().values()
+                .filter(|x| x.needs() > 0)
+                .map(|x| x.pool())
+                .collect();
+            let needs: usize = number_pool.number_pool_items().values().map(|x| x.needs()).sum();
+            let is_disjoint = pools.iter().enumerate().all(|(idx, pool)| pools[idx + 1..].iter().all(|x| pool.is_disjoint(x)));
+            if needs != settings.count() || !is_disjoint {
+                continue;
+            }
+            let bounds: Vec<(usize, usize)> = pools.iter().filter_map(|x| x.bounds()).collect();
+            return Some((bounds.iter().map(|x| x.0).min()?, bounds.iter().map(|x| x.1).max()?));
+        }
+    }
+    return None;
+}
+
+fn estimate(settings: &Settings, expected_rules: &[&dyn RuleTrait], ranges: &[(usize, usize)], rng: &dyn RandomTrait) -> SolutionCount {
+    let space: f64 = ranges.iter().map(|(min, max)| (max - min) as f64 + 1.0).product();
+    let shared_data: HashMap<String, HashMap<String, MapAnyValue>> = HashMap::new();
+    let mut numbers: Vec<usize> = Vec::with_capacity(ranges.len());
+    let mut matches: usize = 0;
+    for _ in 0..ESTIMATE_SAMPLES {
+        numbers.clear();
+        numbers.extend(ranges.iter().map(|(min, max)| rng.get_number(*min, *max)));
+        let current_data = CurrentData::with_rng(&numbers, settings, &shared_data, rng);
+        if expected_rules.iter().all(|x| x.is_match(&current_data).is_ok())
+            && settings.exclude_rules().iter().flatten().all(|x| x.is_excluded(&current_data).is_ok())
+        {
+            matches += 1;
+        }
+    }
+    return SolutionCount::Estimate {
+        count: space * matches as f64 / ESTIMATE_SAMPLES as f64,
+        samples: ESTIMATE_SAMPLES,
+        matches,
+    };
+}
+
+pub(crate) enum CountStop {
+    Overflow,
+    TooManyStates,
+}
+
+pub(crate) trait CountValue: Copy {
+    fn zero() -> Self;
+    fn from_u128(v: u128) -> Self;
+    fn checked_add(self, other: Self) -> Option<Self>;
+    fn checked_mul(self, other: Self) -> Option<Self>;
+}
+
+impl CountValue for u128 {
+    fn zero() -> u128 {
+        return 0;
+    }
+
+    fn from_u128(v: u128) -> u128 {
+        return v;
+    }
+
+    fn checked_add(self, other: u128) -> Option<u128> {
+        return u128::checked_add(self, other);
+    }
+
+    fn checked_mul(self, other: u128) -> Option<u128> {
+        return u128::checked_mul(self, other);
+    }
+}
+
+impl CountValue for f64 {
+    fn zero() -> f64 {
+        return 0.0;
+    }
+
+    fn from_u128(v: u128) -> f64 {
+        return v as f64;
+    }
+
+    fn checked_add(self, other: f64) -> Option<f64> {
+        return Some(self + other).filter(|x| x.is_finite());
+    }
+
+    fn checked_mul(self, other: f64) -> Option<f64> {
+        return Some(self * other).filter(|x| x.is_finite());
+    }
+}
+
+//Numbers that every rule treats the same: same odd/even, same NumberPools and can be used at the same indexes
+#[derive(Debug, Clone)]
+pub(crate) struct NumberClass {
+    odd: bool,
+    indexes: Vec<bool>,
+    pools: Vec<bool>,
+    //Every number of the class is a number of this odd/even in one of these ranges, in order
+    ranges: Vec<(usize, usize)>,
+    len: u128,
+}
+
+impl NumberClass {
+    //Number at rank, numbers are ranked from low to high
+    pub(crate) fn number(&self, mut rank: u128) -> usize {
+        let key = if self.odd { OddEvenKey::Odd } else { OddEvenKey::Even };
+        for (min, max) in &self.ranges {
+            let len = count_key(*min, *max, key) as u128;
+            if rank < len {
+                let first = if OddEven::is_odd(*min) == self.odd { *min } else { min + 1 };
+                return first + 2 * rank as usize;
+            }
+            rank -= len;
+        }
+        panic!("Rank is greater than the {} numbers of {:?}", self.len, self);
+    }
+}
+
+//Counts matches by how many numbers of every NumberClass are used, instead of by the numbers.
+//Only works for rules that depend on those counts, the other rules are left out and is_exact is false.
+#[derive(Debug, Clone)]
+pub(crate) struct ClassModel {
+    count: usize,
+    exact: bool,
+    no_duplicate: bool,
+    classes: Vec<NumberClass>,
+    //Odd and even count of every OddEven
+    odd_evens: Vec<(usize, usize)>,
+    //Needs of every NumberPool item, in the same order as NumberClass::pools
+    pool_needs: Vec<usize>,
+}
+
+impl ClassModel {
+    pub(crate) fn new(settings: &Settings, ranges: &[(usize, usize)]) -> Option<ClassModel> {
+        let mut exact = settings.exclude_rules().as_ref().is_none_or(|x| x.is_empty());
+        let mut no_duplicate = false;
+        let mut number_ranges = 0;
+        let mut odd_evens: Vec<(usize, usize)> = Vec::new();
+        let mut pools: Vec<PoolType> = Vec::new();
+        let mut pool_needs: Vec<usize> = Vec::new();
+        let mut odd_even_by_indexes: Vec<&OddEvenByIndex> = Vec::new();
+        let mut pools_by_index: Vec<(&PoolType, Vec<usize>)> = Vec::new();
+        for expected_rule in settings.expected_rules() {
+            let rule = expected_rule.as_any();
+            if rule.is::<NoDuplicate>() {
+                no_duplicate = true;
+            } else if rule.is::<NumberRange>() {
+                number_ranges += 1;
+            } else if let Some(odd_even) = rule.downcast_ref::<OddEven>() {
+                odd_evens.push((odd_even.odd(), odd_even.even()));
+            } else if let Some(number_pool) = rule.downcast_ref::<NumberPool>() {
+                let mut keys: Vec<&String> = number_pool.number_pool_items().keys().collect();
+                keys.sort_unstable();
+                for key in keys {
+                    pools.push(number_pool.number_pool_items()[key].pool().clone());
+                    pool_needs.push(number_pool.number_pool_items()[key].needs());
+                }
+            } else if let Some(odd_even_by_index) = rule.downcast_ref::<OddEvenByIndex>() {
+                odd_even_by_indexes.push(odd_even_by_index);
+            } else if let Some(number_pool_by_index) = rule.downcast_ref::<NumberPoolByIndex>() {
+                for number_pool_item in number_pool_by_index.number_pool_items() {
+                    pools_by_index.push((number_pool_item.pool(), number_pool_item.indexes().iter().copied().collect()));
+                }
+            } else if !rule.is::<RandomNumber>() {
+                exact = false;
+            }
+        }
+        //The first NumberRange is in ranges, without one ranges hold every number of the pools
+        if number_ranges > 1 {
+            exact = false;
+        }
+        //NumberPool counts different numbers, which only matches the count of numbers without duplicates
+        if !pools.is_empty() && !no_duplicate {
+            exact = false;
+            pools.clear();
+            pool_needs.clear();
+        }
+
+        let count = ranges.len();
+        let is_in_index = |idx: usize, number: usize| -> bool {
+            let (min, max) = ranges[idx];
+            return number >= min
+                && number <= max
+                && odd_even_by_indexes.iter().all(|x| match x.odd_even().get(&idx) {
+                    Some(OddEvenKey::Odd) => OddEven::is_odd(number),
+                    Some(OddEvenKey::Even) => OddEven::is_even(number),
+                    None => true,
+                })
+                && pools_by_index.iter().all(|(pool, indexes)| !indexes.contains(&idx) || pool.contains(number));
+        };
+
+        //Between two cuts every number is in the same ranges and pools
+        let mut cuts: BTreeSet<u128> = BTreeSet::new();
+        let mut add_range = |min: usize, max: usize| {
+            cuts.insert(min as u128);
+            cuts.insert(max as u128 + 1);
+        };
+        for (min, max) in ranges {
+            add_range(*min, *max);
+        }
+        for pool in pools.iter().chain(pools_by_index.iter().map(|x| x.0)) {
+            match pool {
+                PoolType::Set(set) => set.iter().for_each(|x| add_range(*x, *x)),
+                PoolType::MinMax(min, max) => add_range(*min, *max),
+            }
+        }
+        let low = ranges.iter().map(|x| x.0).min()? as u128;
+        let high = ranges.iter().map(|x| x.1).max()? as u128 + 1;
+        let cuts: Vec<u128> = cuts.into_iter().filter(|x| *x >= low && *x <= high).collect();
+
+        let mut classes: Vec<NumberClass> = Vec::new();
+        for window in cuts.windows(2) {
+            let (min, max) = (window[0] as usize, (window[1] - 1) as usize);
+            for key in [OddEvenKey::Odd, OddEvenKey::Even] {
+                let len = count_key(min, max, key);
+                if len == 0 {
+                    continue;
+                }
+                let odd = key == OddEvenKey::Odd;
+                let number = if OddEven::is_odd(min) == odd { min } else { min + 1 };
+                let indexes: Vec<bool> = (0..count).map(|idx| is_in_index(idx, number)).collect();
+                if !indexes.iter().any(|x| *x) {
+                    continue;
+                }
+                let number_pools: Vec<bool> = pools.iter().map(|x| x.contains(number)).collect();
+                match classes.iter_mut().find(|x| x.odd == odd && x.indexes == indexes && x.pools == number_pools) {
+                    Some(class) => {
+                        class.ranges.push((min, max));
+                        class.len += len as u128;
+                    },
+                    None => classes.push(NumberClass { odd, indexes, pools: number_pools, ranges: vec![(min, max)], len: len as u128 }),
+                }
+            }
+        }
+        return Some(ClassModel { count, exact, no_duplicate, classes, odd_evens, pool_needs });
+    }
+
+    //True when every rule is in the model
+    pub(crate) fn is_exact(&self) -> bool {
+        return self.exact;
+    }
+
+    pub(crate) fn count_len(&self) -> usize {
+        return self.count;
+    }
+
+    pub(crate) fn no_duplicate(&self) -> bool {
+        return self.no_duplicate;
+    }
+
+    pub(crate) fn classes(&self) -> &Vec<NumberClass> {
+        return &self.classes;
+    }
+
+    pub(crate) fn empty_state(&self) -> Vec<usize> {
+        return vec![0; self.classes.len()];
+    }
+
+    //Numbers of the class that can still be picked
+    pub(crate) fn choices(&self, state: &[usize], class_idx: usize) -> u128 {
+        let class = &self.classes[class_idx];
+        return if self.no_duplicate { class.len - state[class_idx] as u128 } else { class.len };
+    }
+
+    pub(crate) fn count<T: CountValue>(&self) -> Result<T, CountStop> {
+        let mut total = T::zero();
+        for (state, ways) in self.states::<T>(self.count)? {
+            if self.is_match(&state) {
+                total = total.checked_add(ways.checked_mul(self.weight::<T>(&state)?).ok_or(CountStop::Overflow)?).ok_or(CountStop::Overflow)?;
+            }
+        }
+        return Ok(total);
+    }
+
+    //How many numbers of every class are used by the first `len` indexes, with the number of
+    //ways to pick the classes of those indexes
+    fn states<T: CountValue>(&self, len: usize) -> Result<HashMap<Vec<usize>, T>, CountStop> {
+        let mut states: HashMap<Vec<usize>, T> = HashMap::from([(self.empty_state(), T::from_u128(1))]);
+        for idx in 0..len {
+            let mut next_states: HashMap<Vec<usize>, T> = HashMap::new();
+            for (state, ways) in &states {
+                for (_, next_state) in self.next_states(state, idx) {
+                    let next_ways = match next_states.get(&next_state) {
+                        Some(v) => v.checked_add(*ways).ok_or(CountStop::Overflow)?,
+                        None => *ways,
+                    };
+                    next_states.insert(next_state, next_ways);
+                }
+            }
+            if next_states.len() > MAX_CLASS_STATES {
+                return Err(CountStop::TooManyStates);
+            }
+            states = next_states;
+        }
+        return Ok(states);
+    }
+
+    //Class and state after the number at idx is picked from one of the classes
+    pub(crate) fn next_states(&self, state: &[usize], idx: usize) -> Vec<(usize, Vec<usize>)> {
+        let mut next_states: Vec<(usize, Vec<usize>)> = Vec::new();
+        for (class_idx, class) in self.classes.iter().enumerate() {
+            if !class.indexes[idx] || (self.no_duplicate && state[class_idx] as u128 >= class.len) {
+                continue;
+            }
+            let mut next_state = state.to_vec();
+            next_state[class_idx] += 1;
+            if self.is_within(&next_state) {
+                next_states.push((class_idx, next_state));
+            }
+        }
+        return next_states;
+    }
+
+    //Ways to pick the numbers of every class once the classes of the indexes are picked
+    fn weight<T: CountValue>(&self, state: &[usize]) -> Result<T, CountStop> {
+        let mut weight = T::from_u128(1);
+        for (class, used) in self.classes.iter().zip(state) {
+            for i in 0..*used as u128 {
+                let choices = if self.no_duplicate { class.len - i } else { class.len };
+                weight = weight.checked_mul(T::from_u128(choices)).ok_or(CountStop::Overflow)?;
+            }
+        }
+        return Ok(weight);
+    }
+
+    fn totals(&self, state: &[usize]) -> (usize, usize, Vec<usize>) {
+        let mut odd = 0;
+        let mut even = 0;
+        let mut pools: Vec<usize> = vec![0; self.pool_needs.len()];
+        for (class, used) in self.classes.iter().zip(state) {
+            if class.odd {
+                odd += used;
+            } else {
+                even += used;
+            }
+            for (pool_idx, is_in_pool) in class.pools.iter().enumerate() {
+                if *is_in_pool {
+                    pools[pool_idx] += used;
+                }
+            }
+        }
+        return (odd, even, pools);
+    }
+
+    fn is_within(&self, state: &[usize]) -> bool {
+        let (odd, even, pools) = self.totals(state);
+        return self.odd_evens.iter().all(|x| odd <= x.0 && even <= x.1)
+            && pools.iter().zip(&self.pool_needs).all(|(has, needs)| has <= needs);
+    }
+
+    pub(crate) fn is_match(&self, state: &[usize]) -> bool {
+        let (odd, even, pools) = self.totals(state);
+        return self.odd_evens.iter().all(|x| odd == x.0 && even == x.1) && pools == self.pool_needs;
+    }
+}
@@ -0,0 +1,209 @@
+use crate::cancellation::{Deadline, StopReason};
+use crate::random::{failed_rule, max_failures, CurrentData};
+use crate::random_trait::{shuffle_vec_with_rng, RandomTrait};
+use crate::rules::{MapAnyValue, RuleTrait};
+use crate::settings::Settings;
+use std::collections::HashMap;
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub(crate) enum SolverOutcome {
+    Found(Vec<usize>),
+    Infeasible(String),
+    BadRequest(String),
+    NodeLimit { rule_name: Option<String>, rule_failures: usize, last_numbers: Vec<usize> },
+    Stopped(StopReason),
+}
+
+enum Stop {
+    BadRequest(String),
+    NodeLimit,
+    Stopped(StopReason),
+}
+
+//Depth first search over the numbers of every index.  The numbers an index can have come from
+//the NumberRange of that index, every rule can remove numbers that can never be part of a match
+//(prune_domain) and the full numbers are checked with is_match and the exclude rules.  Every
+//possible combination is visited until a match is found, so when the search runs out of numbers
+//there is no match.
+pub(crate) struct Solver<'a> {
+    settings: &'a Settings,
+    expected_rules: &'a [&'a dyn RuleTrait],
+    rng: &'a dyn RandomTrait,
+    //When no rule cares about the order of the numbers only sorted numbers are searched
+    //and the match is shuffled
+    sorted: bool,
+    nodes: usize,
+    failure_tracker: HashMap<String, usize>,
+    last_numbers: Vec<usize>,
+    deadline: Deadline<'a>,
+}
+
+impl<'a> Solver<'a> {
+    pub(crate) fn new(settings: &'a Settings, expected_rules: &'a [&'a dyn RuleTrait], rng: &'a dyn RandomTrait) -> Solver<'a> {
+        let sorted = settings.is_order_independent();
+        return Solver {
+            settings,
+            expected_rules,
+            rng,
+            sorted,
+            nodes: 0,
+            failure_tracker: HashMap::new(),
+            last_numbers: Vec::new(),
+            deadline: Deadline::new(settings),
+        };
+    }
+
+    pub(crate) fn nodes(&self) -> usize {
+        return self.nodes;
+    }
+
+    //Failures of full numbers by rule name
+    pub(crate) fn failure_tracker(&self) -> &HashMap<String, usize> {
+        return &self.failure_tracker;
+    }
+
+    pub(crate) fn solve(&mut self) -> SolverOutcome {
+        let mut numbers: Vec<usize> = Vec::with_capacity(self.settings.count());
+        return match self.search(&mut numbers) {
+            Ok(true) => {
+                if self.sorted {
+                    shuffle_vec_with_rng(&mut numbers, self.rng);
+                }
+                SolverOutcome::Found(numbers)
+            },
+            Ok(false) => {
+                let mut reason = format!("No numbers match all rules, every combination was searched ({} nodes).", self.nodes);
+                if let (Some(rule_name), rule_failures) = self.most_failed_rule() {
+                    reason.push_str(&format!(" Rule that failed the most: {} ({} times)", rule_name, rule_failures));
+                }
+                SolverOutcome::Infeasible(reason)
+            },
+            Err(Stop::BadRequest(e)) => SolverOutcome::BadRequest(e),
+            Err(Stop::NodeLimit) => {
+                let (rule_name, rule_failures) = self.most_failed_rule();
+                SolverOutcome::NodeLimit { rule_name, rule_failures, last_numbers: self.last_numbers.clone() }
+            },
+            Err(Stop::Stopped(stop_reason)) => SolverOutcome::Stopped(stop_reason),
+        };
+    }
+
+    fn search(&mut self, numbers: &mut Vec<usize>) -> Result<bool, Stop> {
+        if numbers.len() == self.settings.count() {
+            return Ok(self.is_match(numbers));
+        }
+        let mut domain = self.domain(numbers)?;
+        shuffle_vec_with_rng(&mut domain, self.rng);
+        for number in domain {
+            if let Some(stop_reason) = self.deadline.stop_reason() {
+                return Err(Stop::Stopped(stop_reason));
+            }
+            if let Some(max_solver_nodes) = self.settings.max_solver_nodes() {
+                if self.nodes >= max_solver_nodes {
+                    return Err(Stop::NodeLimit);
+                }
+            }
+            self.nodes += 1;
+            numbers.push(number);
+            if self.search(numbers)? {
+                return Ok(true);
+            }
+            numbers.pop();
+        }
+        return Ok(false);
+    }
+
+    //Number of matches, None when finding them all takes more than max_nodes numbers
+    pub(crate) fn count(&mut self, max_nodes: usize) -> Option<u128> {
+        let mut numbers: Vec<usize> = Vec::with_capacity(self.settings.count());
+        return self.count_search(&mut numbers, max_nodes);
+    }
+
+    fn count_search(&mut self, numbers: &mut Vec<usize>, max_nodes: usize) -> Option<u128> {
+        if numbers.len() == self.settings.count() {
+            if !self.is_match(numbers) {
+                return Some(0);
+            }
+            //Every order of sorted numbers matches as well
+            return if self.sorted { permutations(numbers) } else { Some(1) };
+        }
+        let domain = self.domain(numbers).ok()?;
+        let mut total: u128 = 0;
+        for number in domain {
+            if self.nodes >= max_nodes {
+                return None;
+            }
+            self.nodes += 1;
+            numbers.push(number);
+            let count = self.count_search(numbers, max_nodes);
+            numbers.pop();
+            total = total.checked_add(count?)?;
+        }
+        return Some(total);
+    }
+
+    fn domain(&self, numbers: &Vec<usize>) -> Result<Vec<usize>, Stop> {
+        let empty_shared_data: HashMap<String, HashMap<String, MapAnyValue>> = HashMap::new();
+        let current_data_sd = CurrentData::with_rng(numbers, self.settings, &empty_shared_data, self.rng);
+        let mut shared_data: HashMap<String, HashMap<String, MapAnyValue>> = HashMap::new();
+        for expected_rule in self.expected_rules {
+            if let Some(rule_shared_data) = expected_rule.share_data(&current_data_sd) {
+                shared_data.insert(expected_rule.name(), rule_shared_data);
+            }
+        }
+        let (mut min, max) = Settings::get_min_max("NumberRange", &shared_data);
+        if max == usize::MAX {
+            return Err(Stop::BadRequest(format!(
+                "Backtracking solver needs a NumberRange with a max for index {}", numbers.len()
+            )));
+        }
+        if self.sorted {
+            min = min.max(numbers.last().copied().unwrap_or(min));
+        }
+        if min > max {
+            return Ok(Vec::new());
+        }
+        let mut domain: Vec<usize> = (min..=max).collect();
+        let current_data = CurrentData::with_rng(numbers, self.settings, &shared_data, self.rng);
+        for expected_rule in self.expected_rules {
+            if self.sorted {
+                expected_rule.prune_sorted_domain(&current_data, &mut domain);
+            } else {
+                expected_rule.prune_domain(&current_data, &mut domain);
+            }
+            if domain.is_empty() {
+                break;
+            }
+        }
+        return Ok(domain);
+    }
+
+    fn is_match(&mut self, numbers: &Vec<usize>) -> bool {
+        let shared_data: HashMap<String, HashMap<String, MapAnyValue>> = HashMap::new();
+        let current_data = CurrentData::with_rng(numbers, self.settings, &shared_data, self.rng);
+        return match failed_rule(self.expected_rules, &current_data) {
+            Some((rule_name, _)) => {
+                *self.failure_tracker.entry(rule_name).or_insert(0) += 1;
+                self.last_numbers.clone_from(numbers);
+                false
+            },
+            None => true,
+        };
+    }
+
+    fn most_failed_rule(&self) -> (Option<String>, usize) {
+        return max_failures(self.failure_tracker.iter().map(|(rule_name, count)| (rule_name.as_str(), *count)));
+    }
+}
+
+//Number of different orders of sorted numbers
+fn permutations(sorted_numbers: &[usize]) -> Option<u128> {
+    let mut permutations: u128 = 1;
+    let mut total: u128 = 0;
+    for group in sorted_numbers.chunk_by(|a, b| a == b) {
+        for i in 1..=group.len() as u128 {
+            total += 1;
+            permutations = permutations.checked_mul(total)? / i;
+        }
+    }
+    return Some(permutations);
+}
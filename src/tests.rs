@@ -6,9 +6,21 @@ use crate::rules::RuleTrait;
 use crate::rules::*;
 use crate::random::*;
 use crate::settings::*;
+use crate::seeded_random::SeededRandom;
+use crate::replay::*;
+use crate::random_error::RandomError;
+use crate::batch::*;
+use crate::generator::Generator;
+use crate::solutions::*;
+use crate::trace::*;
+use crate::rule_stats::RuleStats;
+use crate::cancellation::CancellationToken;
+use crate::observer::*;
+#[cfg(feature="csprng")]
+use crate::os_random::OsRandom;
 use std::collections::{HashMap, HashSet};
 use std::iter::FromIterator;
-use std::sync::Mutex;
+use std::sync::{Arc, Mutex};
 use once_cell::sync::Lazy;
 #[cfg(not(feature="rand"))]
 use once_cell::sync::OnceCell;
@@ -1271,6 +1283,10 @@ pub struct TestNoDefaultRandom {}
 // }
 
 impl RandomTrait for TestNoDefaultRandom {
+    fn next_u64(&self) -> u64 {
+        return 1;
+    }
+
     fn get_number(&self, _min: usize, _max: usize) -> usize {
         //static ARRAY: OnceLock<Mutex<Vec<u8>>> = OnceLock::new();
         //ARRAY.get_or_init(|| Mutex::new(vec![1,2,3,4,5,6,7,8,9,10,11,12,13,14,15,16,17,18,19,20]));
@@ -1284,4 +1300,1293 @@ impl RandomTrait for TestNoDefaultRandom {
         //return ARRAY.get().unwrap().lock().unwrap().pop().unwrap();
         return true;
     }
-}
\ No newline at end of file
+}
+#[test]
+fn seeded_random_same_seed() {
+    let new_settings = || Settings::new(&[
+        Box::new(NoDuplicate{}),
+        Box::new(NumberPool::new(&[
+            NumberPoolItem::new("must_contain_1_4", &PoolType::Set(HashSet::from_iter([1, 4])), 2),
+            NumberPoolItem::new("some_set", &PoolType::Set(HashSet::from_iter([17, 18, 19, 20, 21])), 3),
+        ])),
+        Box::new(OddEven::new(5, 5)),
+        Box::new(NumberRange::all(1, 100)),
+    ], 10);
+    for seed in 0..20 {
+        let random_result_1 = random_numbers_with_rng(&new_settings(), &SeededRandom::new(seed));
+        let random_result_2 = random_numbers_with_rng(&new_settings(), &SeededRandom::new(seed));
+        assert_eq!(random_result_1.numbers().unwrap(), random_result_2.numbers().unwrap(), "{:?}", random_result_1);
+        assert_eq!(random_result_1.attempts(), random_result_2.attempts());
+        assert_eq!(
+            random_result_1.string_with_rng(true, &SeededRandom::new(seed)),
+            random_result_2.string_with_rng(true, &SeededRandom::new(seed))
+        );
+    }
+}
+
+#[test]
+fn seeded_random_independent_generators() {
+    let settings = Settings::new(&[Box::new(NumberRange::all(1, 100))], 20);
+    let rng_1 = SeededRandom::new(1);
+    let rng_2 = SeededRandom::new(2);
+    let numbers_1 = random_numbers_with_rng(&settings, &rng_1).numbers().unwrap().clone();
+    let numbers_2 = random_numbers_with_rng(&settings, &rng_2).numbers().unwrap().clone();
+    assert_ne!(numbers_1, numbers_2);
+    assert_eq!(numbers_1, random_numbers_with_rng(&settings, &SeededRandom::new(1)).numbers().unwrap().clone());
+}
+
+#[test]
+fn replay_token_reproduces_result() {
+    let new_settings = || Settings::builder(5)
+        .rules(&[
+            Box::new(NoDuplicate{}),
+            Box::new(Sequential::new(3, &[2])),
+            Box::new(NumberPool::new(&[
+                NumberPoolItem::new("some_set", &PoolType::Set(HashSet::from_iter([17, 18, 19, 20, 21])), 2),
+            ])),
+            Box::new(NumberRange::all(1, 30)),
+        ])
+        .record_draws(true)
+        .build()
+        .unwrap();
+    for seed in 0..10 {
+        let random_result = random_numbers_with_rng(&new_settings(), &SeededRandom::new(seed));
+        assert_eq!(random_result.replay_token().seed(), Some(seed));
+        let replay_token: ReplayToken = random_result.replay_token().to_string().parse().unwrap();
+        assert_eq!(&replay_token, random_result.replay_token());
+
+        let replay_result = replay(&new_settings(), &replay_token).unwrap();
+        assert_eq!(random_result.status(), replay_result.status());
+        assert_eq!(random_result.numbers(), replay_result.numbers());
+        assert_eq!(random_result.attempts(), replay_result.attempts());
+        assert_eq!(random_result.replay_token(), replay_result.replay_token());
+
+        let seed_result = replay(&new_settings(), &ReplayToken::new(Some(seed), &[])).unwrap();
+        assert_eq!(random_result.numbers(), seed_result.numbers());
+    }
+
+    //Without record_draws only the seed is kept
+    let settings = Settings::new(&[Box::new(NoDuplicate{}), Box::new(NumberRange::all(1, 30))], 5);
+    let random_result = random_numbers_with_rng(&settings, &SeededRandom::new(3));
+    assert_eq!(random_result.replay_token(), &ReplayToken::new(Some(3), &[]));
+    assert_eq!(random_result.numbers(), replay(&settings, random_result.replay_token()).unwrap().numbers());
+    assert!(random_numbers(&settings).replay_token().draws().is_empty());
+
+    //Failed results of a generator without a seed keep the draws
+    let settings = Settings::builder(4)
+        .rules(&[
+            Box::new(NoDuplicate{}),
+            Box::new(Sequential::new(4, &[])),
+            Box::new(NumberSpace::new(&[NumberSpaceItem::new(&NumberSpaceType::Eq(1), 3)])),
+            Box::new(NumberRange::all(1, 50)),
+        ])
+        .max_tries(20)
+        .build()
+        .unwrap();
+    let random_result = random_numbers(&settings);
+    assert_eq!(random_result.status(), RandomResultType::Failed);
+    assert!(!random_result.replay_token().draws().is_empty());
+    let replay_result = replay(&settings, random_result.replay_token()).unwrap();
+    assert_eq!(replay_result.numbers(), random_result.numbers());
+    assert_eq!(replay_result.attempts(), random_result.attempts());
+}
+
+#[test]
+fn replay_with_other_generator() {
+    let settings = Settings::new(&[Box::new(NoDuplicate{}), Box::new(NumberRange::all(1, 50))], 6);
+    let new_rng = |seed: u64| TestXorShiftRandom { state: Arc::new(Mutex::new(seed)) };
+    let random_result = random_numbers_with_rng(&settings, &new_rng(88172645463325252));
+    assert_eq!(random_result.replay_token().seed(), Some(88172645463325252));
+    let replay_result = replay_with(&settings, random_result.replay_token(), |seed| Box::new(new_rng(seed))).unwrap();
+    assert_eq!(random_result.numbers(), replay_result.numbers());
+}
+
+#[test]
+fn replay_token_uniform_solver() {
+    //101^15 matches are more than 2^64, so the uniform solver draws with next_u64
+    let settings = Settings::builder(15)
+        .rule(Box::new(NumberRange::all(0, 100)))
+        .solver(SolverType::Uniform)
+        .record_draws(true)
+        .build()
+        .unwrap();
+    let random_result = random_numbers_with_rng(&settings, &SeededRandom::new(1));
+    assert_eq!(random_result.status(), RandomResultType::Success);
+    assert!(random_result.replay_token().draws().iter().any(|x| matches!(x, Draw::U64(_))));
+    let replay_token: ReplayToken = random_result.replay_token().to_string().parse().unwrap();
+    assert_eq!(&replay_token, random_result.replay_token());
+    let replay_result = replay(&settings, &ReplayToken::new(None, replay_token.draws())).unwrap();
+    assert_eq!(random_result.numbers(), replay_result.numbers());
+}
+
+#[test]
+fn replay_token_diverged() {
+    let random_result = random_numbers(&Settings::builder(5).rule(Box::new(NumberRange::all(1, 20))).record_draws(true).build().unwrap());
+    assert_eq!(random_result.replay_token().seed(), None);
+    assert_eq!(random_result.replay_token().draws().len(), 5 + 5 * Settings::new(&[Box::new(NumberRange::all(1, 20))], 5).expected_rules().len());
+    assert!(replay(&Settings::new(&[Box::new(NumberRange::all(1, 20))], 5), &ReplayToken::new(None, &[])).is_err());
+    assert!(replay(&Settings::new(&[Box::new(NumberRange::all(1, 50))], 5), random_result.replay_token()).is_err());
+    assert!(replay(&Settings::new(&[Box::new(NumberRange::all(1, 20))], 6), random_result.replay_token()).is_err());
+    assert!("12|n1-x:3".parse::<ReplayToken>().is_err());
+}
+
+#[test]
+fn random_password_requires_secure_rng() {
+    let settings = Settings::new(&[
+        Box::new(NumberPool::alphanumeric_specs(10, 5, 1))
+    ], 16);
+    assert!(random_password(&settings, &SeededRandom::new(1)).is_err());
+    assert!(random_password(&settings, get_random_trait().as_ref()).is_err());
+}
+
+#[test]
+#[cfg(feature="csprng")]
+fn random_password_os_random() {
+    let rng = OsRandom{};
+    assert!(rng.is_cryptographically_secure());
+    let settings = Settings::new(&[
+        Box::new(NoDuplicate{}),
+        Box::new(NumberPool::alphanumeric_with_rng(16, true, &rng))
+    ], 16);
+    let password = random_password(&settings, &rng).unwrap();
+    assert_eq!(password.chars().count(), 16);
+    assert!(password.chars().any(|c| c.is_numeric()));
+    assert!(password.chars().any(|c| NP_SPECIAL_CHAR_SET.lock().unwrap().contains(&c)));
+    assert!(random_numbers_with_rng(&settings, &rng).replay_token().draws().is_empty());
+}
+
+#[test]
+fn random_trait_next_u64_rejects_biased_values() {
+    //Range of 3 has to reject raw values below 2^64 % 3 = 1
+    let rng = TestStreamRandom::new(&[0, 7]);
+    assert_eq!(rng.get_number(10, 12), 11);
+    assert_eq!(rng.idx.lock().unwrap().to_owned(), 2);
+    let rng = TestStreamRandom::new(&[u64::MAX, 0]);
+    assert!(rng.get_bool());
+    assert!(!rng.get_bool());
+    let rng = TestStreamRandom::new(&[u64::MAX]);
+    assert_eq!(rng.get_number(0, usize::MAX), usize::MAX);
+}
+
+#[test]
+fn random_trait_next_u64_unbiased() {
+    let rng = TestXorShiftRandom { state: Arc::new(Mutex::new(88172645463325252)) };
+    let mut counts = [0_usize; 6];
+    for _ in 0..60000 {
+        let number = rng.get_number(5, 10);
+        assert!((5..=10).contains(&number));
+        counts[number - 5] += 1;
+    }
+    assert!(counts.iter().all(|x| (9500..=10500).contains(x)), "{:?}", counts);
+    let random_result = random_numbers_with_rng(&Settings::new(&[
+        Box::new(NoDuplicate{}),
+        Box::new(OddEven::new(3, 2)),
+        Box::new(NumberRange::all(1, 20))
+    ], 5), &rng);
+    assert_eq!(RandomResultType::Success, random_result.status(), "{:?}", random_result);
+}
+
+#[derive(Debug, Clone)]
+pub struct TestStreamRandom {
+    stream: Vec<u64>,
+    idx: Arc<Mutex<usize>>,
+}
+
+impl TestStreamRandom {
+    fn new(stream: &[u64]) -> TestStreamRandom {
+        return TestStreamRandom { stream: stream.to_vec(), idx: Arc::new(Mutex::new(0)) };
+    }
+}
+
+impl RandomTrait for TestStreamRandom {
+    fn next_u64(&self) -> u64 {
+        let mut idx = self.idx.lock().unwrap();
+        *idx += 1;
+        return self.stream[*idx - 1];
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct TestXorShiftRandom {
+    state: Arc<Mutex<u64>>,
+}
+
+impl RandomTrait for TestXorShiftRandom {
+    fn next_u64(&self) -> u64 {
+        let mut x = self.state.lock().unwrap();
+        *x ^= *x << 13;
+        *x ^= *x >> 7;
+        *x ^= *x << 17;
+        return *x;
+    }
+
+    fn seed(&self) -> Option<u64> {
+        return Some(*self.state.lock().unwrap());
+    }
+}
+
+#[test]
+fn random_error_max_tries_exceeded() {
+    //No numbers can be sequential and have a space of 1 between them
+    let random_result = random_numbers(&Settings::new(&[
+        Box::new(NoDuplicate{}),
+        Box::new(Sequential::new(10, &[])),
+        Box::new(NumberSpace::new(&[NumberSpaceItem::new(&NumberSpaceType::Eq(1), 9)])),
+        Box::new(NumberRange::all(1, 100))
+    ], 10));
+    assert_eq!(RandomResultType::Failed, random_result.status());
+    match random_result.numbers() {
+        Err(RandomError::MaxTriesExceeded { attempts, max_tries, rule_name, rule_failures, last_numbers, .. }) => {
+            assert_eq!(attempts, 500);
+            assert_eq!(max_tries, 500);
+            assert!(rule_name.is_some());
+            assert!(rule_failures > 0);
+            assert!(!last_numbers.is_empty());
+        },
+        e => panic!("{:?}", e)
+    }
+    assert_eq!(random_result.numbers().unwrap_err(), random_result.string(false).unwrap_err());
+
+    fn with_question_mark(settings: &Settings) -> std::result::Result<usize, Box<dyn std::error::Error>> {
+        return Ok(random_numbers(settings).numbers()?.len());
+    }
+    assert!(with_question_mark(&Settings::new(&[Box::new(OddEven::new(2, 0)), Box::new(NumberRange::all(2, 2))], 2)).is_err());
+    assert_eq!(with_question_mark(&Settings::new(&[Box::new(NumberRange::all(2, 2))], 2)).unwrap(), 2);
+}
+
+#[test]
+fn halt_from_error_clears_numbers() {
+    let settings = Settings::builder(3).rule(Box::new(NumberRange::all(1, 10))).max_specific_error_count(1).log_level(LogLevel::Info).build().unwrap();
+    let mut logs: Vec<Log> = Vec::new();
+    let mut err_tracker: HashMap<String, usize> = HashMap::new();
+    let mut numbers = vec![1, 2];
+    let mut clear_err_tracker: Vec<usize> = Vec::new();
+    halt_from_error("imc-NumberRange", "too big", &mut logs, &mut err_tracker, 1, &mut numbers, &settings, &mut clear_err_tracker);
+    assert_eq!(numbers, vec![1, 2]);
+    halt_from_error("imc-NumberRange", "too big", &mut logs, &mut err_tracker, 2, &mut numbers, &settings, &mut clear_err_tracker);
+    assert!(numbers.is_empty());
+    assert_eq!(clear_err_tracker, vec![2]);
+    assert!(logs.iter().any(|x| matches!(x, Log::Error { msg } if msg.starts_with("Reset Because Of Too Many Same Errors"))));
+}
+
+#[test]
+fn log_levels() {
+    let rules: [Box<dyn RuleTrait>; 3] = [Box::new(NoDuplicate{}), Box::new(NumberRange::all(1, 20)), Box::new(OddEven::new(3, 2))];
+    let with_log_level = |log_level: LogLevel| Settings::builder(5).rules(&rules).log_level(log_level).build().unwrap();
+
+    //Nothing is logged while numbers are created by default
+    let random_result = random_numbers_with_rng(&Settings::new(&rules, 5), &SeededRandom::new(1));
+    assert_eq!(random_result.status(), RandomResultType::Success);
+    assert!(random_result.logs().is_empty());
+    assert!(!random_result.to_string().contains("Info"));
+
+    let random_result = random_numbers_with_rng(&with_log_level(LogLevel::Info), &SeededRandom::new(1));
+    assert!(matches!(&random_result.logs()[..2], [Log::Info { .. }, Log::Info { msg }] if msg == "Attempt - 1"), "{}", random_result);
+    assert!(!random_result.logs().iter().any(|x| matches!(x, Log::Trace { .. })));
+    let random_result = random_numbers_with_rng(&with_log_level(LogLevel::Trace), &SeededRandom::new(1));
+    assert!(random_result.logs().iter().any(|x| matches!(x, Log::Trace { msg } if msg.starts_with("GEN_TYPE"))));
+    assert!(random_result.to_string().contains("Trace : GEN_TYPE"));
+
+    //Only the last logs are kept
+    let rules: [Box<dyn RuleTrait>; 4] = [
+        Box::new(NoDuplicate{}),
+        Box::new(Sequential::new(10, &[])),
+        Box::new(NumberSpace::new(&[NumberSpaceItem::new(&NumberSpaceType::Eq(1), 9)])),
+        Box::new(NumberRange::all(1, 100)),
+    ];
+    let settings = Settings::builder(10).rules(&rules).max_tries(50).max_is_match_attempts(5).log_level(LogLevel::Info).max_logs(5).build().unwrap();
+    let random_result = random_numbers(&settings);
+    assert_eq!(random_result.logs().len(), 5);
+    assert!(matches!(random_result.logs().last(), Some(Log::Error { msg }) if msg == "Reached max_tries: 50"));
+    let random_result = random_numbers(&Settings::new(&rules, 10));
+    assert_eq!(random_result.logs().len(), 1);
+    let settings = Settings::builder(10).rules(&rules).max_tries(50).max_is_match_attempts(5).log_level(LogLevel::Off).build().unwrap();
+    let random_result = random_numbers(&settings);
+    assert!(random_result.logs().is_empty());
+    assert!(matches!(random_result.numbers(), Err(RandomError::MaxTriesExceeded { .. })));
+}
+
+#[test]
+fn trace_events() {
+    let rules: [Box<dyn RuleTrait>; 3] = [Box::new(NoDuplicate{}), Box::new(NumberRange::all(1, 20)), Box::new(OddEven::new(3, 2))];
+    assert!(random_numbers(&Settings::new(&rules, 5)).trace().is_empty());
+
+    let settings = Settings::builder(5).rules(&rules).log_level(LogLevel::Trace).build().unwrap();
+    let random_result = random_numbers_with_rng(&settings, &SeededRandom::new(1));
+    let trace = random_result.trace();
+    assert_eq!(*trace[0].event(), TraceEvent::AttemptStarted { attempt: 1 });
+    assert!(trace.iter().any(|x| matches!(x.event(), TraceEvent::RuleGenerated { attempt: 1, .. })));
+    assert_eq!(*trace.last().unwrap().event(), TraceEvent::Success {
+        attempt: random_result.attempts(),
+        numbers: random_result.numbers().unwrap().clone()
+    });
+    assert!(trace.windows(2).all(|x| x[0].elapsed() <= x[1].elapsed()));
+
+    let json_lines = random_result.trace_json_lines();
+    assert_eq!(json_lines.lines().count(), trace.len());
+    assert!(json_lines.lines().all(|x| x.starts_with("{\"elapsed_us\":") && x.ends_with('}')));
+    assert!(json_lines.lines().next().unwrap().ends_with(",\"event\":\"attempt_started\",\"attempt\":1}"));
+
+    let settings = Settings::builder(10)
+        .rules(&[
+            Box::new(NoDuplicate{}),
+            Box::new(Sequential::new(10, &[])),
+            Box::new(NumberSpace::new(&[NumberSpaceItem::new(&NumberSpaceType::Eq(1), 9)])),
+            Box::new(NumberRange::all(1, 100)),
+        ])
+        .max_tries(100)
+        .max_is_match_attempts(5)
+        .max_specific_error_count(2)
+        .log_level(LogLevel::Trace)
+        .max_logs(10_000)
+        .build()
+        .unwrap();
+    let random_result = random_numbers(&settings);
+    let events: Vec<&TraceEvent> = random_result.trace().iter().map(|x| x.event()).collect();
+    assert_eq!(events.iter().filter(|x| matches!(x, TraceEvent::AttemptStarted { .. })).count(), 100);
+    assert!(events.iter().any(|x| matches!(x, TraceEvent::WithinRangeFailed { .. })));
+    assert!(events.iter().any(|x| matches!(x, TraceEvent::Reset { .. })));
+    assert!(!events.iter().any(|x| matches!(x, TraceEvent::Success { .. })));
+
+    //Only [4, 1] is not excluded
+    let excluded: HashSet<Vec<usize>> = (1..=4).flat_map(|x| (1..=4).map(move |y| vec![x, y])).filter(|x| *x != [4, 1]).collect();
+    let settings = Settings::builder(2)
+        .rules(&[Box::new(NoDuplicate{}), Box::new(NumberRange::all(1, 4)), Box::new(ExcludeNumberSets::new(&excluded))])
+        .log_level(LogLevel::Trace)
+        .build()
+        .unwrap();
+    let random_result = random_numbers_with_rng(&settings, &SeededRandom::new(3));
+    assert_eq!(random_result.numbers().unwrap(), &vec![4, 1]);
+    assert!(random_result.trace().iter().any(|x| matches!(
+        x.event(),
+        TraceEvent::MatchFailed { rule_name, numbers, .. } if rule_name == "ExcludeNumberSets" && numbers.len() == 2
+    )));
+    assert!(random_result.trace_json_lines().contains("\"event\":\"match_failed\",\"attempt\":"));
+}
+
+#[test]
+fn rule_stats() {
+    let rules: [Box<dyn RuleTrait>; 3] = [Box::new(NoDuplicate{}), Box::new(NumberRange::all(1, 20)), Box::new(OddEven::new(3, 2))];
+    let random_result = random_numbers_with_rng(&Settings::new(&rules, 5), &SeededRandom::new(1));
+    let generated: usize = random_result.rule_stats().values().map(|x| x.generated()).sum();
+    assert!(generated >= 5);
+    assert!(random_result.rule_stats().values().all(|x| x.time().is_zero()));
+
+    let settings = Settings::builder(5).rules(&rules).time_rules(true).build().unwrap();
+    let random_result = random_numbers_with_rng(&settings, &SeededRandom::new(1));
+    assert!(random_result.rule_stats().values().any(|x| !x.time().is_zero()));
+
+    let settings = Settings::builder(10)
+        .rules(&[
+            Box::new(NoDuplicate{}),
+            Box::new(Sequential::new(10, &[])),
+            Box::new(NumberSpace::new(&[NumberSpaceItem::new(&NumberSpaceType::Eq(1), 9)])),
+            Box::new(NumberRange::all(1, 100)),
+        ])
+        .max_tries(200)
+        .max_is_match_attempts(5)
+        .max_specific_error_count(2)
+        .build()
+        .unwrap();
+    let random_result = random_numbers(&settings);
+    let rule_stats = random_result.rule_stats();
+    let rejections: usize = rule_stats.values().map(|x| x.within_range_rejections() + x.match_rejections()).sum();
+    assert!(rejections > 0);
+    assert_eq!(rule_stats.values().map(|x| x.resets()).sum::<usize>(), random_result.clear_err_tracker().len());
+    assert!(rule_stats.get("Sequential").is_some_and(|x| x.within_range_rejections() > 0), "{:?}", rule_stats);
+
+    //Proposed numbers that failed
+    let settings = Settings::builder(3)
+        .rules(&[Box::new(NoDuplicate{}), Box::new(NumberRange::all(1, 7)), Box::new(Sequential::new(1, &[2]))])
+        .solver(SolverType::Uniform)
+        .build()
+        .unwrap();
+    let random_result = random_numbers_with_rng(&settings, &SeededRandom::new(4));
+    assert_eq!(
+        random_result.rule_stats().get("Sequential").map(RuleStats::match_rejections).unwrap_or(0),
+        random_result.attempts() - 1
+    );
+}
+
+#[test]
+fn timeout_and_cancellation() {
+    //No numbers can be sequential and have a space of 1 between them
+    let builder = Settings::builder(10)
+        .rules(&[
+            Box::new(NoDuplicate{}),
+            Box::new(Sequential::new(10, &[])),
+            Box::new(NumberSpace::new(&[NumberSpaceItem::new(&NumberSpaceType::Eq(1), 9)])),
+            Box::new(NumberRange::all(1, 100)),
+        ])
+        .max_tries(usize::MAX);
+
+    let random_result = random_numbers(&builder.clone().timeout(std::time::Duration::from_millis(50)).build().unwrap());
+    assert_eq!(random_result.status(), RandomResultType::TimedOut);
+    assert!(random_result.attempts() > 0);
+    assert!(!random_result.rule_stats().is_empty());
+    assert!(matches!(random_result.numbers(), Err(RandomError::TimedOut { attempts, .. }) if attempts == random_result.attempts()));
+
+    //Spaces between odd numbers are even, the backtracking solver has to search every combination
+    let settings = Settings::builder(10)
+        .rules(&[
+            Box::new(NoDuplicate{}),
+            Box::new(OddEven::new(10, 0)),
+            Box::new(NumberSpace::new(&[NumberSpaceItem::new(&NumberSpaceType::Eq(3), 1)])),
+            Box::new(NumberRange::all(1, 100)),
+        ])
+        .solver(SolverType::Backtracking)
+        .timeout(std::time::Duration::from_millis(50))
+        .build()
+        .unwrap();
+    assert_eq!(random_numbers(&settings).status(), RandomResultType::TimedOut);
+
+    let cancellation_token = CancellationToken::new();
+    let settings = builder.clone().cancellation_token(cancellation_token.clone()).build().unwrap();
+    let cancel = cancellation_token.clone();
+    let handle = std::thread::spawn(move || {
+        std::thread::sleep(std::time::Duration::from_millis(20));
+        cancel.cancel();
+    });
+    let random_result = random_numbers(&settings);
+    handle.join().unwrap();
+    assert_eq!(random_result.status(), RandomResultType::Cancelled);
+    assert!(random_result.attempts() > 0);
+    assert_eq!(random_result.numbers().unwrap_err(), RandomError::Cancelled { attempts: random_result.attempts() });
+
+    //Cancelled before the first attempt
+    let random_result = random_numbers(&settings);
+    assert_eq!(random_result.status(), RandomResultType::Cancelled);
+    assert_eq!(random_result.attempts(), 0);
+
+    assert!(builder.timeout(std::time::Duration::ZERO).build().is_err());
+}
+
+#[derive(Default)]
+struct CountingObserver {
+    attempts: std::sync::atomic::AtomicUsize,
+    candidates: std::sync::atomic::AtomicUsize,
+    rejections: Mutex<HashMap<RejectionKind, usize>>,
+    resets: std::sync::atomic::AtomicUsize,
+    finished: Mutex<Vec<RandomResultType>>,
+}
+
+impl GenerationObserver for CountingObserver {
+    fn on_attempt(&self, _attempt: usize) {
+        self.attempts.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+    }
+
+    fn on_candidate(&self, _attempt: usize, _rule_name: &str, numbers: &[usize], selected: &[usize]) {
+        assert!(!numbers.is_empty() && selected.ends_with(numbers));
+        self.candidates.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+    }
+
+    fn on_rule_rejection(&self, _attempt: usize, _rule_name: &str, kind: RejectionKind, _reason: &str) {
+        *self.rejections.lock().unwrap().entry(kind).or_insert(0) += 1;
+    }
+
+    fn on_reset(&self, _attempt: usize, _rule_name: &str, _reason: &str) {
+        self.resets.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+    }
+
+    fn on_finish(&self, random_result: &RandomResult) {
+        self.finished.lock().unwrap().push(random_result.status());
+    }
+}
+
+#[test]
+fn generation_observer() {
+    let observer = Arc::new(CountingObserver::default());
+    let settings = Settings::builder(10)
+        .rules(&[
+            Box::new(NoDuplicate{}),
+            Box::new(Sequential::new(10, &[])),
+            Box::new(NumberSpace::new(&[NumberSpaceItem::new(&NumberSpaceType::Eq(1), 9)])),
+            Box::new(NumberRange::all(1, 100)),
+        ])
+        .max_tries(200)
+        .max_is_match_attempts(5)
+        .max_specific_error_count(2)
+        .observer(observer.clone())
+        .build()
+        .unwrap();
+    let random_result = random_numbers_with_rng(&settings, &SeededRandom::new(1));
+    assert_eq!(observer.attempts.load(std::sync::atomic::Ordering::Relaxed), random_result.attempts());
+    assert!(observer.candidates.load(std::sync::atomic::Ordering::Relaxed) > 0);
+    assert!(observer.rejections.lock().unwrap().get(&RejectionKind::WithinRange).is_some_and(|x| *x > 0));
+    assert_eq!(observer.resets.load(std::sync::atomic::Ordering::Relaxed), random_result.clear_err_tracker().len());
+    assert_eq!(*observer.finished.lock().unwrap(), vec![RandomResultType::Failed]);
+
+    //Proposed numbers that failed
+    let observer = Arc::new(CountingObserver::default());
+    let settings = Settings::builder(3)
+        .rules(&[Box::new(NoDuplicate{}), Box::new(NumberRange::all(1, 7)), Box::new(Sequential::new(1, &[2]))])
+        .solver(SolverType::Uniform)
+        .observer(observer.clone())
+        .build()
+        .unwrap();
+    let random_result = random_numbers_with_rng(&settings, &SeededRandom::new(4));
+    assert_eq!(observer.attempts.load(std::sync::atomic::Ordering::Relaxed), random_result.attempts());
+    assert_eq!(observer.rejections.lock().unwrap().get(&RejectionKind::Match).copied().unwrap_or(0), random_result.attempts() - 1);
+    assert_eq!(*observer.finished.lock().unwrap(), vec![RandomResultType::Success]);
+
+    //on_finish is called once for every result
+    let observer = Arc::new(CountingObserver::default());
+    let settings = Settings::builder(5).rule(Box::new(NumberRange::all(1, 20))).observer(observer.clone()).build().unwrap();
+    let _ = random_numbers(&settings);
+    let _ = random_numbers(&settings);
+    assert_eq!(observer.finished.lock().unwrap().len(), 2);
+}
+
+#[cfg(feature="tracing")]
+#[derive(Default)]
+struct CountingSubscriber {
+    next_id: std::sync::atomic::AtomicU64,
+    spans: Mutex<Vec<String>>,
+    events: Mutex<Vec<String>>,
+}
+
+#[cfg(feature="tracing")]
+struct MessageVisitor<'a>(&'a mut String);
+
+#[cfg(feature="tracing")]
+impl tracing::field::Visit for MessageVisitor<'_> {
+    fn record_debug(&mut self, field: &tracing::field::Field, value: &dyn std::fmt::Debug) {
+        if field.name() == "message" {
+            self.0.push_str(&format!("{:?}", value));
+        }
+    }
+}
+
+#[cfg(feature="tracing")]
+impl tracing::Subscriber for CountingSubscriber {
+    fn enabled(&self, _metadata: &tracing::Metadata<'_>) -> bool {
+        return true;
+    }
+
+    fn new_span(&self, span: &tracing::span::Attributes<'_>) -> tracing::span::Id {
+        self.spans.lock().unwrap().push(span.metadata().name().to_owned());
+        return tracing::span::Id::from_u64(self.next_id.fetch_add(1, std::sync::atomic::Ordering::Relaxed) + 1);
+    }
+
+    fn record(&self, _span: &tracing::span::Id, _values: &tracing::span::Record<'_>) {}
+
+    fn record_follows_from(&self, _span: &tracing::span::Id, _follows: &tracing::span::Id) {}
+
+    fn event(&self, event: &tracing::Event<'_>) {
+        let mut message = String::new();
+        event.record(&mut MessageVisitor(&mut message));
+        self.events.lock().unwrap().push(message);
+    }
+
+    fn enter(&self, _span: &tracing::span::Id) {}
+
+    fn exit(&self, _span: &tracing::span::Id) {}
+}
+
+#[cfg(feature="tracing")]
+#[test]
+fn tracing_spans_and_events() {
+    let settings = Settings::builder(10)
+        .rules(&[
+            Box::new(NoDuplicate{}),
+            Box::new(Sequential::new(10, &[])),
+            Box::new(NumberSpace::new(&[NumberSpaceItem::new(&NumberSpaceType::Eq(1), 9)])),
+            Box::new(NumberRange::all(1, 100)),
+        ])
+        .max_tries(200)
+        .max_is_match_attempts(5)
+        .max_specific_error_count(2)
+        .build()
+        .unwrap();
+    let subscriber = Arc::new(CountingSubscriber::default());
+    let random_result = tracing::subscriber::with_default(subscriber.clone(), || {
+        return random_numbers_with_rng(&settings, &SeededRandom::new(1));
+    });
+    let spans = subscriber.spans.lock().unwrap();
+    assert_eq!(spans.iter().filter(|x| *x == "random_numbers").count(), 1);
+    assert_eq!(spans.iter().filter(|x| *x == "attempt").count(), random_result.attempts());
+    let events = subscriber.events.lock().unwrap();
+    assert!(events.iter().any(|x| x == "rule rejected numbers"));
+    assert_eq!(events.iter().filter(|x| *x == "numbers were reset").count(), random_result.clear_err_tracker().len());
+    assert_eq!(events.iter().filter(|x| *x == "numbers were not created").count(), 1);
+}
+
+#[test]
+fn targeted_backtracking() {
+    let settings = Settings::new(&[Box::new(NumberRange::all(1, 5))], 4);
+    let shared_data: HashMap<String, HashMap<String, MapAnyValue>> = HashMap::new();
+    let numbers = vec![3, 9, 3, 6];
+    let current_data = CurrentData::new(&numbers, &settings, &shared_data);
+    assert_eq!(NoDuplicate{}.violating_positions(&current_data), Some(vec![2]));
+    assert_eq!(NumberRange::all(1, 5).violating_positions(&current_data), Some(vec![1, 3]));
+    assert_eq!(OddEven::new(1, 3).violating_positions(&current_data), Some(vec![0, 1]));
+    assert_eq!(Sequential::new(1, &[]).violating_positions(&current_data), None);
+    //A fixed number stays, the other copy is removed
+    let fixed_settings = settings.with_fixed_numbers(&[None, None, Some(3)]);
+    let current_data = CurrentData::new(&numbers, &fixed_settings, &shared_data);
+    assert_eq!(NoDuplicate{}.violating_positions(&current_data), Some(vec![0]));
+
+    //Long sequences keep the numbers that did not cause the error
+    let settings = Settings::builder(150)
+        .rules(&[Box::new(NoDuplicate{}), Box::new(NumberRange::all(1, 200)), Box::new(OddEven::new(75, 75))])
+        .max_tries(MAX_TRIES)
+        .max_specific_error_count(8)
+        .log_level(LogLevel::Info)
+        .max_logs(usize::MAX)
+        .build()
+        .unwrap();
+    let random_result = random_numbers_with_rng(&settings, &SeededRandom::new(1));
+    assert_eq!(random_result.status(), RandomResultType::Success, "{:?}", random_result.numbers());
+    assert!(random_result.logs().iter().any(|x| x.to_string().contains("REMOVE - Positions")));
+    assert!(!random_result.clear_err_tracker().is_empty());
+}
+
+#[test]
+fn complete_numbers_with_fixed() {
+    let rules: [Box<dyn RuleTrait>; 3] = [Box::new(NoDuplicate{}), Box::new(NumberRange::all(1, 20)), Box::new(OddEven::new(3, 3))];
+    let settings = Settings::new(&rules, 6);
+    let shared_data: HashMap<String, HashMap<String, MapAnyValue>> = HashMap::new();
+    let is_match = |numbers: &Vec<usize>| {
+        let current_data = CurrentData::new(numbers, &settings, &shared_data);
+        return settings.expected_rules().iter().all(|x| x.is_match(&current_data).is_ok());
+    };
+    for seed in 0..20 {
+        //Prefix
+        let random_result = complete_numbers_with_rng(&settings, &[Some(7), Some(8)], &SeededRandom::new(seed));
+        let numbers = random_result.numbers().unwrap();
+        assert_eq!(&numbers[..2], &[7, 8]);
+        assert!(is_match(numbers), "{:?}", numbers);
+
+        //Fixed positions
+        let random_result = complete_numbers_with_rng(&settings, &[None, Some(2), None, None, Some(19)], &SeededRandom::new(seed));
+        let numbers = random_result.numbers().unwrap();
+        assert_eq!((numbers[1], numbers[4]), (2, 19));
+        assert!(is_match(numbers), "{:?}", numbers);
+    }
+
+    let random_result = complete_numbers(&settings, &[Some(1), Some(2), Some(3), Some(4), Some(5), Some(6)]);
+    assert_eq!(random_result.numbers().unwrap(), &vec![1, 2, 3, 4, 5, 6]);
+    assert_eq!(random_result.attempts(), 0);
+
+    //The given values already break a rule
+    let random_result = complete_numbers(&settings, &[Some(1), Some(3), Some(5), Some(7)]);
+    assert_eq!(random_result.status(), RandomResultType::Infeasible);
+    assert!(matches!(random_result.numbers(), Err(RandomError::FixedNumbersRejected { rule_name, .. }) if rule_name == "OddEven"));
+    let random_result = complete_numbers(&settings, &[None, None, Some(21)]);
+    assert!(matches!(random_result.numbers(), Err(RandomError::FixedNumbersRejected { rule_name, .. }) if rule_name == "NumberRange"));
+    let random_result = complete_numbers(&settings, &[Some(4), None, None, Some(4)]);
+    assert!(matches!(random_result.numbers(), Err(RandomError::FixedNumbersRejected { rule_name, .. }) if rule_name == "NoDuplicate"));
+
+    assert_eq!(complete_numbers(&settings, &[None; 7]).status(), RandomResultType::BadRequest);
+    let settings = Settings::builder(6).rules(&rules).solver(SolverType::Backtracking).build().unwrap();
+    assert_eq!(complete_numbers(&settings, &[Some(1)]).status(), RandomResultType::BadRequest);
+}
+
+#[test]
+fn reroll_selected_positions() {
+    let digits: HashSet<usize> = (1..=9).collect();
+    let settings = Settings::with_exclude_rules(&[
+        Box::new(NoDuplicate{}),
+        Box::new(NumberRange::from_map(&[(&[0, 1, 2], 1, 9), (&[3, 4, 5], 20, 40)])),
+        Box::new(OddEvenByIndex::new(&[0, 3], &[1, 4])),
+        Box::new(NumberPoolByIndex::new(vec![NumberPoolItemByIndex::new("digits", &PoolType::Set(digits), &HashSet::from_iter(vec![2]))])),
+        Box::new(ExcludeNumberSets::new(&HashSet::from_iter(vec![vec![1, 2, 3, 21, 22, 23]]))),
+    ], 6, Some(vec![Box::new(NumberRange::from_map(&[(&[5], 30, 40)]))]));
+    let shared_data: HashMap<String, HashMap<String, MapAnyValue>> = HashMap::new();
+    let is_match = |numbers: &Vec<usize>| {
+        let current_data = CurrentData::new(numbers, &settings, &shared_data);
+        return settings.expected_rules().iter().all(|x| x.is_match(&current_data).is_ok())
+            && settings.exclude_rules().iter().flatten().all(|x| x.is_excluded(&current_data).is_ok());
+    };
+    let random_result = random_numbers_with_rng(&settings, &SeededRandom::new(1));
+    let numbers = random_result.numbers().unwrap().to_vec();
+    assert!(is_match(&numbers), "{:?}", numbers);
+    let mut changed = false;
+    for seed in 0..20 {
+        let rerolled = reroll_numbers_with_rng(&settings, &numbers, &[1, 3], &SeededRandom::new(seed));
+        let rerolled = rerolled.numbers().unwrap();
+        assert!(is_match(rerolled), "{:?}", rerolled);
+        assert_eq!((rerolled[0], rerolled[2], rerolled[4], rerolled[5]), (numbers[0], numbers[2], numbers[4], numbers[5]));
+        changed |= rerolled != &numbers;
+    }
+    assert!(changed);
+
+    let rerolled = random_result.reroll(&settings, &[5]).unwrap();
+    assert!(is_match(rerolled.numbers().unwrap()));
+    assert_eq!(rerolled.numbers().unwrap()[..5], numbers[..5]);
+
+    assert_eq!(reroll_numbers(&settings, &numbers, &[6]).status(), RandomResultType::BadRequest);
+    assert_eq!(reroll_numbers(&settings, &numbers[..5], &[1]).status(), RandomResultType::BadRequest);
+}
+
+#[test]
+fn settings_validate() {
+    //Passwords of 8 printable chars without duplicates, 2 digits and no digit first
+    let settings = Settings::with_exclude_rules(&[
+        Box::new(NoDuplicate{}),
+        Box::new(NumberRange::all(33, 126)),
+        Box::new(NumberPool::new(&[NumberPoolItem::new("digits", &PoolType::new(&"0123456789".chars().collect()), 2)])),
+    ], 8, Some(vec![Box::new(NumberRange::from_map(&[(&[0], 48, 57)]))]));
+    assert_eq!(settings.validate_str("ab1cd2ef"), Ok(()));
+    match settings.validate_str("1aab c") {
+        Err(RandomError::RuleViolations { violations }) => {
+            let rule_names: Vec<&str> = violations.iter().map(|x| x.rule_name()).collect();
+            assert_eq!(rule_names, vec!["Count", "NoDuplicate", "NumberRange", "NumberPool", "exr-NumberRange"]);
+            assert!(violations.iter().all(|x| !x.message().is_empty()));
+        },
+        e => panic!("{:?}", e),
+    }
+
+    let random_result = random_numbers(&settings);
+    assert_eq!(settings.validate(random_result.numbers().unwrap()), Ok(()));
+    assert_eq!(settings.validate_str(&random_result.string(false).unwrap()), Ok(()));
+
+    assert!(matches!(Settings::new(&[Box::new(OddEven::new(5, 5))], 5).validate(&[1, 2, 3, 4, 5]), Err(RandomError::BadRequest { .. })));
+}
+
+#[test]
+fn random_error_invalid_char_code() {
+    let random_result = random_numbers(&Settings::new(&[
+        Box::new(NumberRange::from_map(&[(&[0], 65, 65), (&[1], 0xD800, 0xD800)]))
+    ], 2));
+    assert_eq!(Err(RandomError::InvalidCharCode { number: 0xD800, idx: 1 }), random_result.string(false));
+}
+
+#[test]
+fn settings_bad_request() {
+    let rules: Vec<Box<dyn RuleTrait>> = vec![
+        Box::new(OddEven::new(5, 5)),
+        Box::new(Sequential::new(3, &[3])),
+        Box::new(NumberPool::new(&[NumberPoolItem::new("some_set", &PoolType::MinMax(1, 10), 6)])),
+        Box::new(NumberRange::all(1, 100))
+    ];
+    match Settings::try_new(&rules, 5) {
+        Err(RandomError::BadRequest { errors }) => assert_eq!(errors.len(), 3, "{:?}", errors),
+        _ => panic!("Should be a bad request"),
+    }
+    assert!(Settings::try_with_exclude_rules(&rules, 10, Some(vec![Box::new(NoDuplicate{})])).is_ok());
+
+    let random_result = random_numbers(&Settings::new(&rules, 5));
+    assert_eq!(RandomResultType::BadRequest, random_result.status());
+    assert_eq!(random_result.attempts(), 0);
+    match random_result.numbers() {
+        Err(RandomError::BadRequest { errors }) => assert_eq!(&errors, Settings::new(&rules, 5).bad_request_errors()),
+        e => panic!("{:?}", e),
+    }
+}
+
+#[test]
+fn settings_builder() {
+    let settings = Settings::builder(10)
+        .rule(Box::new(NoDuplicate{}))
+        .rules(&[Box::new(Sequential::new(10, &[])), Box::new(NumberSpace::new(&[NumberSpaceItem::new(&NumberSpaceType::Eq(1), 9)]))])
+        .exclude_rule(Box::new(NumberRange::all(2, 2)))
+        .max_tries(50)
+        .max_is_match_attempts(5)
+        .max_specific_error_count(7)
+        .build()
+        .unwrap();
+    assert_eq!(settings.count(), 10);
+    assert_eq!(settings.max_tries(), 50);
+    assert_eq!(settings.max_is_match_attempts(), 5);
+    assert_eq!(settings.max_specific_error_count(), 7);
+    assert_eq!(settings.expected_rules().len(), 4);
+    assert_eq!(settings.exclude_rules().as_ref().unwrap().len(), 1);
+    assert_eq!(random_numbers(&settings).attempts(), 50);
+
+    let settings = Settings::builder(5).rule(Box::new(NumberRange::all(1, 3))).build().unwrap();
+    assert_eq!(settings.max_tries(), 500);
+    assert_eq!(settings.max_is_match_attempts(), 15);
+    assert_eq!(settings.max_specific_error_count(), 20);
+    assert!(settings.exclude_rules().is_none());
+
+    match Settings::builder(4).rule(Box::new(OddEven::new(3, 3))).max_tries(0).max_specific_error_count(0).build() {
+        Err(RandomError::BadRequest { errors }) => assert_eq!(errors.len(), 4, "{:?}", errors),
+        _ => panic!("Should be a bad request"),
+    }
+}
+
+#[test]
+fn random_numbers_batch_distinct() {
+    let settings = Settings::new(&[Box::new(NoDuplicate{}), Box::new(NumberRange::all(1, 100))], 5);
+    let batch_result = random_numbers_batch(&settings, 200);
+    assert_eq!(batch_result.results().len(), 200);
+    assert_eq!(batch_result.missing(), 0);
+    assert_eq!(batch_result.numbers().into_iter().collect::<HashSet<&Vec<usize>>>().len(), 200);
+
+    //Only 45 different sets of 2 numbers exist between 1 and 10
+    let settings = Settings::new(&[Box::new(NoDuplicate{}), Box::new(NumberRange::all(1, 10))], 2);
+    let batch_result = random_numbers_batch_with_rng(&settings, 60, true, &SeededRandom::new(7));
+    assert!(batch_result.missing() >= 15);
+    assert!(batch_result.duplicates() > 0);
+    assert_eq!(batch_result.results().len() + batch_result.missing(), 60);
+    let sets: HashSet<Vec<usize>> = batch_result.numbers().into_iter().map(|x| {
+        let mut v = x.clone();
+        v.sort_unstable();
+        v
+    }).collect();
+    assert_eq!(sets.len(), batch_result.results().len());
+    for random_result in batch_result.results() {
+        assert_eq!(random_result.numbers(), replay(&settings, random_result.replay_token()).unwrap().numbers());
+    }
+
+    let batch_result = random_numbers_batch(&Settings::new(&[Box::new(OddEven::new(3, 0))], 2), 3);
+    assert_eq!(batch_result.missing(), 3);
+    assert!(matches!(batch_result.error(), Some(RandomError::BadRequest { .. })));
+
+    //Without retries every duplicate is missing
+    let settings = Settings::builder(2).rules(&[Box::new(NoDuplicate{}), Box::new(NumberRange::all(1, 10))]).max_duplicate_retries(0).build().unwrap();
+    let batch_result = random_numbers_batch_with_rng(&settings, 60, true, &SeededRandom::new(7));
+    assert_eq!(batch_result.missing(), batch_result.duplicates());
+
+    //The batch stops at the first result that can not change by trying again
+    let observer = Arc::new(CountingObserver::default());
+    let settings = Settings::builder(4)
+        .rules(&[Box::new(NoDuplicate{}), Box::new(NumberRange::all(1, 3))])
+        .observer(observer.clone())
+        .build()
+        .unwrap();
+    let batch_result = random_numbers_batch(&settings, 5);
+    assert_eq!(batch_result.missing(), 5);
+    assert!(matches!(batch_result.error(), Some(RandomError::Infeasible { .. })));
+    assert_eq!(*observer.finished.lock().unwrap(), vec![RandomResultType::Infeasible]);
+}
+
+#[test]
+fn backtracking_solver() {
+    //Only 5 sets of numbers match: 6 numbers in a row between 1 and 10
+    let settings = Settings::builder(6)
+        .rules(&[
+            Box::new(NoDuplicate{}),
+            Box::new(NumberRange::all(1, 10)),
+            Box::new(OddEven::new(3, 3)),
+            Box::new(Sequential::new(0, &[6])),
+            Box::new(NumberSpace::new(&[NumberSpaceItem::new(&NumberSpaceType::Eq(1), 5)])),
+        ])
+        .solver(SolverType::Backtracking)
+        .build()
+        .unwrap();
+    for seed in 0..20 {
+        let random_result = random_numbers_with_rng(&settings, &SeededRandom::new(seed));
+        let mut numbers = random_result.numbers().unwrap().clone();
+        assert_eq!(numbers, *replay(&settings, random_result.replay_token()).unwrap().numbers().unwrap());
+        numbers.sort_unstable();
+        assert!(numbers[0] >= 1 && numbers[0] <= 5, "{:?}", numbers);
+        assert_eq!(numbers, (numbers[0]..numbers[0] + 6).collect::<Vec<usize>>());
+    }
+
+    //Index based rules are searched in index order
+    let settings = Settings::builder(4)
+        .rules(&[
+            Box::new(NoDuplicate{}),
+            Box::new(NumberRange::from_map(&[(&[0, 1], 1, 4), (&[2, 3], 5, 6)])),
+            Box::new(OddEvenByIndex::new(&[0, 2], &[1, 3])),
+        ])
+        .solver(SolverType::Backtracking)
+        .build()
+        .unwrap();
+    let numbers = random_numbers(&settings).into_numbers().unwrap();
+    assert!(OddEven::is_odd(numbers[0]) && numbers[0] <= 4, "{:?}", numbers);
+    assert!(OddEven::is_even(numbers[1]) && numbers[1] <= 4, "{:?}", numbers);
+    assert_eq!(numbers[2..], [5, 6]);
+
+    //Proves there is no match instead of running out of tries
+    let settings = Settings::builder(4)
+        .rules(&[
+            Box::new(NoDuplicate{}),
+            Box::new(NumberRange::all(1, 8)),
+            Box::new(Sequential::new(4, &[])),
+            Box::new(NumberSpace::new(&[NumberSpaceItem::new(&NumberSpaceType::Eq(1), 3)])),
+        ])
+        .solver(SolverType::Backtracking)
+        .build()
+        .unwrap();
+    assert!(settings.analyze().is_ok());
+    let random_result = random_numbers(&settings);
+    assert_eq!(random_result.status(), RandomResultType::Infeasible);
+    assert!(matches!(random_result.numbers(), Err(RandomError::Infeasible { .. })));
+
+    //Sequential and NumberSpace remove numbers while searching, so this is proven quickly
+    let settings = Settings::builder(6)
+        .rules(&[
+            Box::new(NoDuplicate{}),
+            Box::new(NumberRange::all(1, 40)),
+            Box::new(Sequential::new(6, &[])),
+            Box::new(NumberSpace::new(&[NumberSpaceItem::new(&NumberSpaceType::Eq(1), 5)])),
+        ])
+        .solver(SolverType::Backtracking)
+        .max_solver_nodes(10_000)
+        .build()
+        .unwrap();
+    let random_result = random_numbers(&settings);
+    assert_eq!(random_result.status(), RandomResultType::Infeasible);
+    assert!(random_result.attempts() < 10_000, "{}", random_result.attempts());
+
+    let settings = Settings::builder(4)
+        .rules(&[Box::new(NoDuplicate{}), Box::new(NumberRange::all(1, 50))])
+        .solver(SolverType::Backtracking)
+        .max_solver_nodes(2)
+        .build()
+        .unwrap();
+    let random_result = random_numbers(&settings);
+    assert_eq!(random_result.status(), RandomResultType::Undecided);
+    assert!(matches!(random_result.numbers(), Err(RandomError::Undecided { nodes: 2, max_solver_nodes: 2, .. })));
+    assert_eq!(Settings::builder(4).build().unwrap().max_solver_nodes(), None);
+
+    //Every index needs a max to search
+    let settings = Settings::builder(3).rule(Box::new(NoDuplicate{})).solver(SolverType::Backtracking).build().unwrap();
+    assert_eq!(random_numbers(&settings).status(), RandomResultType::BadRequest);
+}
+
+#[test]
+fn settings_analyze() {
+    let settings = Settings::new(&[
+        Box::new(NoDuplicate{}),
+        Box::new(OddEven::new(5, 5)),
+        Box::new(NumberRange::all(1, 3))
+    ], 10);
+    match settings.analyze() {
+        Err(RandomError::Infeasible { reason }) => {
+            assert!(reason.contains("NoDuplicate needs 10 different numbers but NumberRange [(0, (1, 3))] only has 3"), "{}", reason);
+            assert!(reason.contains("OddEven needs 5 Odd numbers but NumberRange [(0, (1, 3))] only has 2"), "{}", reason);
+            assert!(reason.contains("OddEven needs 5 Even numbers but NumberRange [(0, (1, 3))] only has 1"), "{}", reason);
+        },
+        e => panic!("{:?}", e)
+    }
+    let random_result = random_numbers(&settings);
+    assert_eq!(random_result.status(), RandomResultType::Infeasible);
+    assert_eq!(random_result.attempts(), 0);
+    assert_eq!(random_result.logs().len(), 1);
+
+    let infeasible = |rules: &[Box<dyn RuleTrait>], count: usize, expected: &str| {
+        match Settings::new(rules, count).analyze() {
+            Err(RandomError::Infeasible { reason }) => assert!(reason.contains(expected), "{}", reason),
+            e => panic!("{} {:?}", expected, e)
+        }
+    };
+    infeasible(&[Box::new(NumberRange::all(1, 10)), Box::new(OddEven::new(2, 2))], 5, "less than count: 5");
+    infeasible(&[Box::new(NumberRange::from_map(&[(&[0, 1], 2, 2)])), Box::new(OddEven::new(1, 1))], 2, "needs 1 Odd numbers");
+    infeasible(&[
+        Box::new(NumberRange::all(1, 20)),
+        Box::new(NumberPool::new(&[NumberPoolItem::new("low", &PoolType::MinMax(15, 30), 7)]))
+    ], 7, "NumberPool low needs 7 different numbers but only 6 of them");
+    infeasible(&[
+        Box::new(NumberRange::from_map(&[(&[0], 1, 9), (&[1], 4, 4)])),
+        Box::new(OddEvenByIndex::new(&[1], &[]))
+    ], 2, "OddEvenByIndex needs a Odd number at index 1");
+    infeasible(&[
+        Box::new(NumberRange::from_map(&[(&[0, 1], 1, 9)])),
+        Box::new(NumberPoolByIndex::new(vec![NumberPoolItemByIndex::new("high", &PoolType::MinMax(10, 20), &HashSet::from([1]))]))
+    ], 2, "NumberPoolByIndex high has no numbers for index 1");
+    infeasible(&[Box::new(NumberRange::all(1, 5)), Box::new(Sequential::new(0, &[6]))], 6, "needs 6 numbers in a row");
+    infeasible(&[Box::new(NumberRange::all(1, 100)), Box::new(Sequential::new(1, &[3, 2]))], 6, "ascending order");
+    infeasible(&[Box::new(NoDuplicate{}), Box::new(NumberRange::all(1, 5)), Box::new(Sequential::new(2, &[2]))], 4, "keep 3 groups apart");
+
+    assert!(Settings::new(&[Box::new(NoDuplicate{}), Box::new(NumberRange::all(1, 6)), Box::new(Sequential::new(2, &[2]))], 4).analyze().is_ok());
+    assert!(Settings::new(&[Box::new(NumberRange::all(1, 3)), Box::new(OddEven::new(5, 5))], 10).analyze().is_ok());
+    assert!(Settings::new(&[Box::new(OddEven::new(5, 5))], 10).analyze().is_ok());
+}
+
+//Counts by checking every number vector of the NumberRange
+fn count_solutions_brute_force(settings: &Settings, ranges: &[(usize, usize)]) -> u128 {
+    return matches_brute_force(settings, ranges).len() as u128;
+}
+
+fn matches_brute_force(settings: &Settings, ranges: &[(usize, usize)]) -> Vec<Vec<usize>> {
+    let shared_data: HashMap<String, HashMap<String, MapAnyValue>> = HashMap::new();
+    let mut numbers: Vec<usize> = ranges.iter().map(|x| x.0).collect();
+    let mut matches: Vec<Vec<usize>> = Vec::new();
+    loop {
+        let current_data = CurrentData::new(&numbers, settings, &shared_data);
+        if settings.expected_rules().iter().all(|x| x.is_match(&current_data).is_ok()) {
+            matches.push(numbers.clone());
+        }
+        let mut idx = 0;
+        while idx < numbers.len() && numbers[idx] == ranges[idx].1 {
+            numbers[idx] = ranges[idx].0;
+            idx += 1;
+        }
+        if idx == numbers.len() {
+            return matches;
+        }
+        numbers[idx] += 1;
+    }
+}
+
+#[test]
+fn settings_count_solutions() {
+    let count = |rules: &[Box<dyn RuleTrait>], count: usize| Settings::new(rules, count).count_solutions().unwrap();
+    assert_eq!(count(&[Box::new(NumberRange::all(1, 10))], 3), SolutionCount::Exact(1000));
+    assert_eq!(count(&[Box::new(NoDuplicate{}), Box::new(NumberRange::all(1, 10))], 3), SolutionCount::Exact(720));
+    assert_eq!(count(&[Box::new(NoDuplicate{}), Box::new(NumberRange::all(1, 10)), Box::new(OddEven::new(2, 1))], 3), SolutionCount::Exact(300));
+    assert_eq!(count(&[
+        Box::new(NoDuplicate{}),
+        Box::new(NumberRange::all(1, 20)),
+        Box::new(NumberPool::new(&[NumberPoolItem::new("low", &PoolType::MinMax(1, 5), 2)]))
+    ], 3), SolutionCount::Exact(900));
+    assert_eq!(count(&[
+        Box::new(NoDuplicate{}),
+        Box::new(NumberRange::from_map(&[(&[0, 1, 2], 1, 6)])),
+        Box::new(OddEvenByIndex::new(&[0], &[1]))
+    ], 3), SolutionCount::Exact(36));
+    assert_eq!(count(&[
+        Box::new(NumberRange::all(1, 10)),
+        Box::new(NumberPoolByIndex::new(vec![NumberPoolItemByIndex::new("first", &PoolType::Set(HashSet::from([2, 3, 11])), &HashSet::from([0]))]))
+    ], 2), SolutionCount::Exact(20));
+
+    let ranges = [(1, 9), (2, 7), (1, 9), (4, 12)];
+    let settings = Settings::new(&[
+        Box::new(NoDuplicate{}),
+        Box::new(NumberRange::from_map(&[(&[0, 2], 1, 9), (&[1], 2, 7), (&[3], 4, 12)])),
+        Box::new(OddEven::new(2, 2)),
+        Box::new(NumberPool::new(&[
+            NumberPoolItem::new("a", &PoolType::MinMax(3, 8), 2),
+            NumberPoolItem::new("b", &PoolType::Set(HashSet::from([1, 2, 11])), 1)
+        ])),
+        Box::new(OddEvenByIndex::new(&[0], &[])),
+        Box::new(NumberPoolByIndex::new(vec![NumberPoolItemByIndex::new("c", &PoolType::MinMax(5, 12), &HashSet::from([3]))]))
+    ], 4);
+    assert_eq!(settings.count_solutions().unwrap(), SolutionCount::Exact(count_solutions_brute_force(&settings, &ranges)));
+
+    //Sequential is counted by searching every combination
+    let settings = Settings::new(&[Box::new(NoDuplicate{}), Box::new(NumberRange::all(1, 10)), Box::new(Sequential::new(0, &[3]))], 3);
+    assert_eq!(settings.count_solutions().unwrap(), SolutionCount::Exact(48));
+    let settings = Settings::new(&[
+        Box::new(NumberRange::from_map(&[(&[0, 1, 2], 1, 7)])),
+        Box::new(OddEvenByIndex::new(&[0], &[2])),
+        Box::new(Sequential::new(1, &[2]))
+    ], 3);
+    assert_eq!(settings.count_solutions().unwrap(), SolutionCount::Exact(count_solutions_brute_force(&settings, &[(1, 7); 3])));
+    let settings = Settings::new(&[
+        Box::new(NoDuplicate{}),
+        Box::new(NumberRange::all(1, 9)),
+        Box::new(Sequential::new(1, &[2])),
+        Box::new(NumberSpace::new(&[NumberSpaceItem::new(&NumberSpaceType::Eq(1), 1), NumberSpaceItem::new(&NumberSpaceType::Gte(3), 1)]))
+    ], 3);
+    assert_eq!(settings.count_solutions().unwrap(), SolutionCount::Exact(count_solutions_brute_force(&settings, &[(1, 9); 3])));
+    let settings = Settings::new(&[
+        Box::new(NoDuplicate{}),
+        Box::new(NumberRange::all(1, 12)),
+        Box::new(Sequential::new(2, &[2])),
+        Box::new(NumberSpace::new(&[NumberSpaceItem::new(&NumberSpaceType::Eq(1), 1), NumberSpaceItem::new(&NumberSpaceType::Lt(4), 2)]))
+    ], 4);
+    assert_eq!(settings.count_solutions().unwrap(), SolutionCount::Exact(count_solutions_brute_force(&settings, &[(1, 12); 4])));
+
+    let solution_count = count(&[Box::new(NumberRange::all(1, 1000))], 20);
+    assert!(matches!(solution_count, SolutionCount::Approximate(_)));
+    assert!((solution_count.log2() - 20.0 * 1000_f64.log2()).abs() < 1e-6);
+
+    let solution_count = count_solutions_with_rng(
+        &Settings::new(&[Box::new(NoDuplicate{}), Box::new(NumberRange::all(1, 1000)), Box::new(Sequential::new(3, &[]))], 3),
+        &SeededRandom::new(3)
+    ).unwrap();
+    assert!(!solution_count.is_exact());
+    assert!((solution_count.count() / 991_025_976.0 - 1.0).abs() < 0.05, "{}", solution_count);
+
+    assert_eq!(count(&[Box::new(NoDuplicate{}), Box::new(NumberRange::all(1, 3))], 4), SolutionCount::Exact(0));
+    assert!(matches!(Settings::new(&[Box::new(NoDuplicate{})], 3).count_solutions(), Err(RandomError::Uncountable { .. })));
+
+    //Without a NumberRange the numbers come from the pools
+    assert_eq!(count(&[Box::new(NoDuplicate{}), Box::new(NumberPool::alphanumeric_specs(3, 1, 0))], 4), SolutionCount::Exact(22100 * 10 * 24));
+    let pool_by_index = || NumberPoolByIndex::new(vec![
+        NumberPoolItemByIndex::new("first", &PoolType::Set(HashSet::from([2, 3, 11])), &HashSet::from([0])),
+        NumberPoolItemByIndex::new("rest", &PoolType::MinMax(4, 9), &HashSet::from([1, 2])),
+    ]);
+    let settings = Settings::new(&[Box::new(NoDuplicate{}), Box::new(OddEven::new(1, 2)), Box::new(pool_by_index())], 3);
+    assert_eq!(settings.count_solutions().unwrap(), SolutionCount::Exact(count_solutions_brute_force(&settings, &[(2, 11), (4, 9), (4, 9)])));
+    //A number outside the pools fits when the needs do not add up to the count
+    let number_pool = NumberPool::new(&[NumberPoolItem::new("low", &PoolType::MinMax(1, 5), 2)]);
+    assert!(matches!(Settings::new(&[Box::new(number_pool)], 3).count_solutions(), Err(RandomError::Uncountable { .. })));
+}
+
+#[test]
+fn uniform_solver() {
+    //Every match is picked about the same number of times
+    let assert_uniform = |rules: &[Box<dyn RuleTrait>], ranges: &[(usize, usize)]| {
+        let settings = Settings::builder(ranges.len()).rules(rules).solver(SolverType::Uniform).build().unwrap();
+        let matches = matches_brute_force(&settings, ranges);
+        let mut picked: HashMap<Vec<usize>, usize> = HashMap::new();
+        let rng = SeededRandom::new(11);
+        for _ in 0..matches.len() * 400 {
+            let random_result = random_numbers_with_rng(&settings, &rng);
+            *picked.entry(random_result.into_numbers().unwrap()).or_insert(0) += 1;
+        }
+        assert_eq!(picked.len(), matches.len());
+        for numbers in &matches {
+            assert!(picked[numbers] > 300 && picked[numbers] < 500, "{:?} {}", numbers, picked[numbers]);
+        }
+    };
+    assert_uniform(&[
+        Box::new(NoDuplicate{}),
+        Box::new(NumberRange::from_map(&[(&[0, 1], 1, 5), (&[2], 3, 8)])),
+        Box::new(OddEven::new(2, 1)),
+        Box::new(NumberPool::new(&[NumberPoolItem::new("low", &PoolType::MinMax(1, 3), 1)])),
+    ], &[(1, 5), (1, 5), (3, 8)]);
+    //Sequential is not counted, the numbers are checked and proposed again
+    assert_uniform(&[
+        Box::new(NoDuplicate{}),
+        Box::new(NumberRange::all(1, 7)),
+        Box::new(OddEvenByIndex::new(&[0], &[])),
+        Box::new(Sequential::new(1, &[2])),
+    ], &[(1, 7); 3]);
+
+    let settings = Settings::builder(6)
+        .rules(&[Box::new(NoDuplicate{}), Box::new(NumberRange::all(1, 49)), Box::new(OddEven::new(3, 3))])
+        .solver(SolverType::Uniform)
+        .build()
+        .unwrap();
+    let random_result = random_numbers_with_rng(&settings, &SeededRandom::new(5));
+    assert_eq!(random_result.attempts(), 1);
+    assert_eq!(random_result.numbers().unwrap(), replay(&settings, random_result.replay_token()).unwrap().numbers().unwrap());
+
+    //Index 0 has to be odd and from a pool of even numbers
+    let settings = Settings::builder(2)
+        .rules(&[
+            Box::new(NumberRange::all(1, 10)),
+            Box::new(OddEvenByIndex::new(&[0], &[])),
+            Box::new(NumberPoolByIndex::new(vec![NumberPoolItemByIndex::new("even", &PoolType::Set(HashSet::from([2, 4])), &HashSet::from([0]))])),
+        ])
+        .solver(SolverType::Uniform)
+        .build()
+        .unwrap();
+    assert_eq!(random_numbers(&settings).status(), RandomResultType::Infeasible);
+
+    let settings = Settings::builder(2).rules(&[Box::new(NoDuplicate{})]).solver(SolverType::Uniform).build().unwrap();
+    assert_eq!(random_numbers(&settings).status(), RandomResultType::BadRequest);
+}
+
+#[test]
+fn settings_entropy() {
+    let settings = Settings::builder(3)
+        .rules(&[Box::new(NoDuplicate{}), Box::new(NumberRange::all(1, 10))])
+        .solver(SolverType::Uniform)
+        .build()
+        .unwrap();
+    let entropy_report = settings.entropy_with_rng(&SeededRandom::new(1)).unwrap();
+    assert!((entropy_report.bits() - 720_f64.log2()).abs() < 1e-9);
+    assert!((entropy_report.range_bits() - 1000_f64.log2()).abs() < 1e-9);
+    assert_eq!(*entropy_report.solution_count(), SolutionCount::Exact(720));
+    assert!(entropy_report.is_uniform());
+    assert!(!entropy_report.is_below_min());
+    assert_eq!(entropy_report.warnings().len(), 1);
+    assert!(entropy_report.warnings()[0].contains("not cryptographically secure"));
+
+    //A seeded rng has no more entropy than its seed
+    let settings = Settings::builder(20).rules(&[Box::new(NumberRange::all(1, 1000))]).build().unwrap();
+    let entropy_report = settings.entropy_with_rng(&SeededRandom::new(1)).unwrap();
+    assert_eq!(entropy_report.bits(), 64.0);
+    assert!(entropy_report.rule_bits() > 199.0);
+    assert!(!entropy_report.is_uniform());
+    assert_eq!(entropy_report.warnings().len(), 2);
+
+    //Excluded sets and exact pool counts take entropy away
+    let rules: [Box<dyn RuleTrait>; 4] = [
+        Box::new(NoDuplicate{}),
+        Box::new(NumberRange::all(1, 6)),
+        Box::new(NumberPool::new(&[NumberPoolItem::new("low", &PoolType::MinMax(1, 2), 2)])),
+        Box::new(ExcludeNumberSets::new(&HashSet::from([vec![1, 2, 3], vec![2, 1, 3]]))),
+    ];
+    let settings = Settings::builder(3).rules(&rules).min_entropy_bits(6.0).build().unwrap();
+    let entropy_report = settings.entropy_with_rng(&SeededRandom::new(1)).unwrap();
+    assert_eq!(*entropy_report.solution_count(), SolutionCount::Exact(22));
+    assert!((entropy_report.rule_bits() - 22_f64.log2()).abs() < 1e-9);
+    assert!(entropy_report.is_below_min());
+    assert!(entropy_report.warnings().last().unwrap().contains("below min_entropy_bits"), "{}", entropy_report);
+
+    let random_result = random_numbers_with_rng(&settings, &SeededRandom::new(2));
+    assert_eq!(random_result.entropy(), Some(&entropy_report));
+    assert!(random_numbers_with_rng(&Settings::new(&rules, 3), &SeededRandom::new(2)).entropy().is_none());
+    assert!(Settings::builder(3).rules(&rules).min_entropy_bits(-1.0).build().is_err());
+
+    //Passwords without a NumberRange take the ranges from the pools
+    let settings = Settings::builder(10)
+        .rules(&[Box::new(NoDuplicate{}), Box::new(NumberPool::alphanumeric_specs(8, 1, 1))])
+        .min_entropy_bits(40.0)
+        .build()
+        .unwrap();
+    let random_result = random_numbers_with_rng(&settings, &SeededRandom::new(2));
+    let entropy_report = random_result.entropy().unwrap();
+    assert!(entropy_report.solution_count().is_exact());
+    assert!((entropy_report.range_bits() - 10.0 * 90_f64.log2()).abs() < 1e-9);
+    let pool_by_index = NumberPoolByIndex::new(vec![
+        NumberPoolItemByIndex::new("alpha", &PoolType::new(&NP_ALPHABET_SET.lock().unwrap()), &HashSet::from([0, 1, 2])),
+        NumberPoolItemByIndex::new("numeric", &PoolType::new(&NP_NUMERIC_SET.lock().unwrap()), &HashSet::from([3])),
+    ]);
+    let settings = Settings::builder(4).rule(Box::new(pool_by_index)).min_entropy_bits(20.0).build().unwrap();
+    let random_result = random_numbers_with_rng(&settings, &SeededRandom::new(2));
+    assert_eq!(*random_result.entropy().unwrap().solution_count(), SolutionCount::Exact(52 * 52 * 52 * 10));
+
+    //The numbers are kept when there is no report, the reason is logged
+    let settings = Settings::builder(2).rule(Box::new(NumberRange::from_map(&[(&[0], 1, 5)]))).min_entropy_bits(1.0).build().unwrap();
+    let random_result = random_numbers_with_rng(&settings, &SeededRandom::new(1));
+    assert_eq!(random_result.status(), RandomResultType::Success);
+    assert!(random_result.entropy().is_none());
+    assert!(matches!(random_result.logs().last(), Some(Log::Error { msg }) if msg.starts_with("No entropy report for min_entropy_bits")));
+}
+
+#[cfg(feature="parallel")]
+#[test]
+fn random_numbers_batch_parallel_distinct() {
+    fn assert_send_sync<T: Send + Sync>() {}
+    assert_send_sync::<Settings>();
+
+    let settings = Settings::new(&[Box::new(NoDuplicate{}), Box::new(NumberRange::all(1, 100)), Box::new(OddEven::new(2, 3))], 5);
+    let batch_result = random_numbers_batch_parallel(&settings, 200);
+    assert_eq!(batch_result.results().len(), 200);
+    assert_eq!(batch_result.missing(), 0);
+    assert_eq!(batch_result.numbers().into_iter().collect::<HashSet<&Vec<usize>>>().len(), 200);
+
+    //Every thread has its own stream, all of them come from the seed
+    let settings = Settings::new(&[Box::new(NoDuplicate{}), Box::new(NumberRange::all(1, 10))], 2);
+    let batch_result = random_numbers_batch_parallel_with_rng(&settings, 60, true, 4, &SeededRandom::new(7));
+    assert!(batch_result.missing() >= 15);
+    assert_eq!(batch_result.results().len() + batch_result.missing(), 60);
+    let sets: HashSet<Vec<usize>> = batch_result.numbers().into_iter().map(|x| {
+        let mut v = x.clone();
+        v.sort_unstable();
+        v
+    }).collect();
+    assert_eq!(sets.len(), batch_result.results().len());
+    for random_result in batch_result.results() {
+        assert_eq!(random_result.numbers(), replay(&settings, random_result.replay_token()).unwrap().numbers());
+    }
+
+    let settings = Settings::new(&[Box::new(NoDuplicate{}), Box::new(NumberRange::all(1, 1000))], 3);
+    let a = random_numbers_batch_parallel_with_rng(&settings, 50, false, 3, &SeededRandom::new(11));
+    let b = random_numbers_batch_parallel_with_rng(&settings, 50, false, 3, &SeededRandom::new(11));
+    assert_eq!(a.numbers(), b.numbers());
+
+    //Duplicates across threads are dropped in thread order, so a crowded batch is the same every time
+    let settings = Settings::new(&[Box::new(NoDuplicate{}), Box::new(NumberRange::all(1, 10))], 2);
+    let a = random_numbers_batch_parallel_with_rng(&settings, 40, true, 4, &SeededRandom::new(3));
+    for _ in 0..10 {
+        let b = random_numbers_batch_parallel_with_rng(&settings, 40, true, 4, &SeededRandom::new(3));
+        assert_eq!(a.numbers(), b.numbers());
+        assert_eq!((a.missing(), a.duplicates()), (b.missing(), b.duplicates()));
+    }
+}
+
+#[test]
+fn settings_iter() {
+    let settings = Settings::new(&[Box::new(NoDuplicate{}), Box::new(NumberRange::all(1, 20))], 3);
+    let numbers_list = settings.iter().take(10).collect::<std::result::Result<Vec<Vec<usize>>, RandomError>>().unwrap();
+    assert_eq!(numbers_list.len(), 10);
+    assert!(numbers_list.iter().all(|x| x.len() == 3));
+
+    let all_even: Vec<Vec<usize>> = settings.iter()
+        .filter_map(|x| x.ok())
+        .filter(|x| x.iter().all(|y| y % 2 == 0))
+        .take(2)
+        .collect();
+    assert_eq!(all_even.len(), 2);
+
+    let rng = SeededRandom::new(3);
+    let expected: Vec<Vec<usize>> = (0..5).map(|_| random_numbers_with_rng(&settings, &rng).into_numbers().unwrap()).collect();
+    let rng = SeededRandom::new(3);
+    for (a, b) in Generator::with_rng(&settings, &rng).zip(expected.iter()) {
+        assert_eq!(&a.unwrap(), b);
+    }
+
+    let bad_settings = Settings::new(&[Box::new(OddEven::new(3, 0))], 2);
+    assert!(matches!(bad_settings.iter().next(), Some(Err(RandomError::BadRequest { .. }))));
+}
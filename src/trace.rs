@@ -0,0 +1,154 @@
+use crate::settings::{LogLevel, Settings};
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
+
+//What happened while numbers were created, only recorded with LogLevel::Trace
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum TraceEvent {
+    AttemptStarted { attempt: usize },
+    //numbers are the numbers of the rule, selected are all numbers with them
+    RuleGenerated { attempt: usize, rule_name: String, numbers: Vec<usize>, selected: Vec<usize> },
+    WithinRangeFailed { attempt: usize, rule_name: String, reason: String, make_priority: bool },
+    MatchFailed { attempt: usize, rule_name: String, reason: String, numbers: Vec<usize> },
+    //Numbers were removed because the same error happened too many times
+    Reset { attempt: usize, rule_name: String, reason: String },
+    Success { attempt: usize, numbers: Vec<usize> },
+}
+
+impl TraceEvent {
+    pub fn name(&self) -> &'static str {
+        return match self {
+            TraceEvent::AttemptStarted { .. } => "attempt_started",
+            TraceEvent::RuleGenerated { .. } => "rule_generated",
+            TraceEvent::WithinRangeFailed { .. } => "within_range_failed",
+            TraceEvent::MatchFailed { .. } => "match_failed",
+            TraceEvent::Reset { .. } => "reset",
+            TraceEvent::Success { .. } => "success",
+        };
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct TraceEntry {
+    elapsed: Duration,
+    event: TraceEvent,
+}
+
+impl TraceEntry {
+    //Time from the start of creating the numbers to the event
+    pub fn elapsed(&self) -> Duration {
+        return self.elapsed;
+    }
+
+    pub fn event(&self) -> &TraceEvent {
+        return &self.event;
+    }
+
+    //One JSON object without new lines, the fields of the event with "event" and "elapsed_us"
+    pub fn to_json(&self) -> String {
+        let mut json = format!("{{\"elapsed_us\":{},\"event\":\"{}\"", self.elapsed.as_micros(), self.event.name());
+        match &self.event {
+            TraceEvent::AttemptStarted { attempt } => {
+                push_number(&mut json, "attempt", *attempt);
+            },
+            TraceEvent::RuleGenerated { attempt, rule_name, numbers, selected } => {
+                push_number(&mut json, "attempt", *attempt);
+                push_str(&mut json, "rule_name", rule_name);
+                push_numbers(&mut json, "numbers", numbers);
+                push_numbers(&mut json, "selected", selected);
+            },
+            TraceEvent::WithinRangeFailed { attempt, rule_name, reason, make_priority } => {
+                push_number(&mut json, "attempt", *attempt);
+                push_str(&mut json, "rule_name", rule_name);
+                push_str(&mut json, "reason", reason);
+                json.push_str(&format!(",\"make_priority\":{}", make_priority));
+            },
+            TraceEvent::MatchFailed { attempt, rule_name, reason, numbers } => {
+                push_number(&mut json, "attempt", *attempt);
+                push_str(&mut json, "rule_name", rule_name);
+                push_str(&mut json, "reason", reason);
+                push_numbers(&mut json, "numbers", numbers);
+            },
+            TraceEvent::Reset { attempt, rule_name, reason } => {
+                push_number(&mut json, "attempt", *attempt);
+                push_str(&mut json, "rule_name", rule_name);
+                push_str(&mut json, "reason", reason);
+            },
+            TraceEvent::Success { attempt, numbers } => {
+                push_number(&mut json, "attempt", *attempt);
+                push_numbers(&mut json, "numbers", numbers);
+            },
+        }
+        json.push('}');
+        return json;
+    }
+}
+
+//One JSON object per line for every entry
+pub fn to_json_lines(entries: &[TraceEntry]) -> String {
+    let mut json_lines = String::new();
+    for entry in entries {
+        json_lines.push_str(&entry.to_json());
+        json_lines.push('\n');
+    }
+    return json_lines;
+}
+
+fn push_number(json: &mut String, key: &str, value: usize) {
+    json.push_str(&format!(",\"{}\":{}", key, value));
+}
+
+fn push_numbers(json: &mut String, key: &str, values: &[usize]) {
+    let values: Vec<String> = values.iter().map(|x| x.to_string()).collect();
+    json.push_str(&format!(",\"{}\":[{}]", key, values.join(",")));
+}
+
+fn push_str(json: &mut String, key: &str, value: &str) {
+    json.push_str(&format!(",\"{}\":\"", key));
+    for c in value.chars() {
+        match c {
+            '"' => json.push_str("\\\""),
+            '\\' => json.push_str("\\\\"),
+            '\n' => json.push_str("\\n"),
+            '\r' => json.push_str("\\r"),
+            '\t' => json.push_str("\\t"),
+            c if (c as u32) < 0x20 => json.push_str(&format!("\\u{:04x}", c as u32)),
+            c => json.push(c),
+        }
+    }
+    json.push('"');
+}
+
+//Records the events when the log level is Trace, like the logs only the last max_logs are kept
+pub(crate) struct TraceRecorder {
+    start: Option<Instant>,
+    max_entries: usize,
+    entries: VecDeque<TraceEntry>,
+}
+
+impl TraceRecorder {
+    pub(crate) fn new(settings: &Settings) -> TraceRecorder {
+        let is_enabled = settings.log_level().includes(LogLevel::Trace) && settings.max_logs() > 0;
+        return TraceRecorder {
+            start: if is_enabled { Some(Instant::now()) } else { None },
+            max_entries: settings.max_logs(),
+            entries: VecDeque::new(),
+        };
+    }
+
+    //Only creates the event when it is recorded
+    pub(crate) fn push(&mut self, event: impl FnOnce() -> TraceEvent) {
+        let start = match self.start {
+            Some(v) => v,
+            None => return,
+        };
+        if self.entries.len() >= self.max_entries {
+            self.entries.pop_front();
+        }
+        self.entries.push_back(TraceEntry { elapsed: start.elapsed(), event: event() });
+    }
+
+    pub(crate) fn into_entries(self) -> Vec<TraceEntry> {
+        return Vec::from(self.entries);
+    }
+}
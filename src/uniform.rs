@@ -0,0 +1,197 @@
+use crate::cancellation::{Deadline, StopReason};
+use crate::observer::RejectionKind;
+use crate::random::{failed_rule, max_failures, CurrentData};
+use crate::random_trait::RandomTrait;
+use crate::rules::{MapAnyValue, RuleTrait};
+use crate::settings::Settings;
+use crate::solutions::{index_ranges, ClassModel, MAX_CLASS_STATES};
+use std::collections::HashMap;
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub(crate) enum UniformOutcome {
+    Found(Vec<usize>),
+    Infeasible(String),
+    BadRequest(String),
+    MaxTries { rule_name: Option<String>, rule_failures: usize, last_numbers: Vec<usize> },
+    Stopped(StopReason),
+}
+
+//Picks every number vector that matches the rules with the same chance.  The numbers are
+//proposed with the same chance from the numbers the ClassModel allows: the matches of every
+//index are counted, one rank is drawn and turned back into numbers.  Rules the ClassModel does
+//not have are checked on the proposed numbers and on a failure the numbers are thrown away and
+//proposed again, which keeps the same chance for every match.  When the matches can not be
+//counted the numbers are proposed from the NumberRange of every index.
+pub(crate) struct UniformSampler<'a> {
+    settings: &'a Settings,
+    expected_rules: &'a [&'a dyn RuleTrait],
+    rng: &'a dyn RandomTrait,
+    attempts: usize,
+    failure_tracker: HashMap<String, usize>,
+    last_numbers: Vec<usize>,
+}
+
+//Matches of every state of every index, layers[idx] has the states after idx numbers
+struct CountTable {
+    model: ClassModel,
+    layers: Vec<HashMap<Vec<usize>, u128>>,
+}
+
+impl<'a> UniformSampler<'a> {
+    pub(crate) fn new(settings: &'a Settings, expected_rules: &'a [&'a dyn RuleTrait], rng: &'a dyn RandomTrait) -> UniformSampler<'a> {
+        return UniformSampler { settings, expected_rules, rng, attempts: 0, failure_tracker: HashMap::new(), last_numbers: Vec::new() };
+    }
+
+    pub(crate) fn attempts(&self) -> usize {
+        return self.attempts;
+    }
+
+    //Failures of proposed numbers by rule name
+    pub(crate) fn failure_tracker(&self) -> &HashMap<String, usize> {
+        return &self.failure_tracker;
+    }
+
+    pub(crate) fn sample(&mut self) -> UniformOutcome {
+        let ranges = match index_ranges(self.settings) {
+            Ok(v) if v.iter().all(|x| x.1 != usize::MAX) => v,
+            _ => return UniformOutcome::BadRequest(String::from("Uniform solver needs a NumberRange with a max or pools for every index")),
+        };
+        let table = ClassModel::new(self.settings, &ranges).and_then(CountTable::new);
+        if let Some(table) = &table {
+            if table.total() == 0 {
+                return UniformOutcome::Infeasible(String::from("No numbers match all rules, every combination was counted."));
+            }
+        }
+        let deadline = Deadline::new(self.settings);
+        let mut numbers: Vec<usize> = Vec::with_capacity(ranges.len());
+        for _ in 0..self.settings.max_tries() {
+            if let Some(stop_reason) = deadline.stop_reason() {
+                return UniformOutcome::Stopped(stop_reason);
+            }
+            self.attempts += 1;
+            #[cfg(feature="tracing")]
+            let _attempt_span = tracing::debug_span!("attempt", attempt = self.attempts).entered();
+            if let Some(observer) = self.settings.observer() {
+                observer.on_attempt(self.attempts);
+            }
+            numbers.clear();
+            match &table {
+                Some(table) => table.unrank(random_below(self.rng, table.total()), &mut numbers),
+                None => numbers.extend(ranges.iter().map(|(min, max)| self.rng.get_number(*min, *max))),
+            }
+            if self.is_match(&numbers) {
+                return UniformOutcome::Found(numbers);
+            }
+        }
+        let (rule_name, rule_failures) = self.most_failed_rule();
+        return UniformOutcome::MaxTries { rule_name, rule_failures, last_numbers: self.last_numbers.clone() };
+    }
+
+    fn is_match(&mut self, numbers: &Vec<usize>) -> bool {
+        let shared_data: HashMap<String, HashMap<String, MapAnyValue>> = HashMap::new();
+        let current_data = CurrentData::with_rng(numbers, self.settings, &shared_data, self.rng);
+        return match failed_rule(self.expected_rules, &current_data) {
+            Some((rule_name, reason)) => {
+                if let Some(observer) = self.settings.observer() {
+                    observer.on_rule_rejection(self.attempts, &rule_name, RejectionKind::Match, &reason);
+                }
+                #[cfg(feature="tracing")]
+                tracing::debug!(attempt = self.attempts, rule_name = %rule_name, kind = ?RejectionKind::Match, reason = %reason, "rule rejected numbers");
+                *self.failure_tracker.entry(rule_name).or_insert(0) += 1;
+                self.last_numbers.clone_from(numbers);
+                false
+            },
+            None => true,
+        };
+    }
+
+    fn most_failed_rule(&self) -> (Option<String>, usize) {
+        return max_failures(self.failure_tracker.iter().map(|(rule_name, count)| (rule_name.as_str(), *count)));
+    }
+}
+
+impl CountTable {
+    //None when there are too many states or too many matches to count in u128
+    fn new(model: ClassModel) -> Option<CountTable> {
+        let len = model.count_len();
+        let mut layers: Vec<HashMap<Vec<usize>, u128>> = vec![HashMap::from([(model.empty_state(), 0)])];
+        for idx in 0..len {
+            let mut next_layer: HashMap<Vec<usize>, u128> = HashMap::new();
+            for state in layers[idx].keys() {
+                for (_, next_state) in model.next_states(state, idx) {
+                    next_layer.insert(next_state, 0);
+                }
+            }
+            if next_layer.len() > MAX_CLASS_STATES {
+                return None;
+            }
+            layers.push(next_layer);
+        }
+        for (state, matches) in layers[len].iter_mut() {
+            *matches = u128::from(model.is_match(state));
+        }
+        for idx in (0..len).rev() {
+            let (head, tail) = layers.split_at_mut(idx + 1);
+            for (state, matches) in head[idx].iter_mut() {
+                let mut total: u128 = 0;
+                for (class_idx, next_state) in model.next_states(state, idx) {
+                    let next_matches = model.choices(state, class_idx).checked_mul(tail[0][&next_state])?;
+                    total = total.checked_add(next_matches)?;
+                }
+                *matches = total;
+            }
+        }
+        return Some(CountTable { model, layers });
+    }
+
+    fn total(&self) -> u128 {
+        return self.layers[0][&self.model.empty_state()];
+    }
+
+    //Numbers of rank, every rank below total is a different number vector
+    fn unrank(&self, mut rank: u128, numbers: &mut Vec<usize>) {
+        let mut state = self.model.empty_state();
+        //Ranks of the used numbers of every class, sorted
+        let mut used: Vec<Vec<u128>> = vec![Vec::new(); self.model.classes().len()];
+        for idx in 0..self.model.count_len() {
+            for (class_idx, next_state) in self.model.next_states(&state, idx) {
+                let next_matches = self.layers[idx + 1][&next_state];
+                let matches = self.model.choices(&state, class_idx) * next_matches;
+                if rank >= matches {
+                    rank -= matches;
+                    continue;
+                }
+                let mut number_rank = rank / next_matches;
+                rank %= next_matches;
+                //Skip the used numbers, which are not in choices
+                if self.model.no_duplicate() {
+                    for used_rank in &used[class_idx] {
+                        if *used_rank <= number_rank {
+                            number_rank += 1;
+                        }
+                    }
+                    let pos = used[class_idx].partition_point(|x| *x < number_rank);
+                    used[class_idx].insert(pos, number_rank);
+                }
+                numbers.push(self.model.classes()[class_idx].number(number_rank));
+                state = next_state;
+                break;
+            }
+        }
+    }
+}
+
+//Unbiased number from 0 to bound - 1
+fn random_below(rng: &dyn RandomTrait, bound: u128) -> u128 {
+    if bound - 1 <= usize::MAX as u128 {
+        return rng.get_number(0, (bound - 1) as usize) as u128;
+    }
+    //Reject the values that would make the modulo biased
+    let threshold = bound.wrapping_neg() % bound;
+    loop {
+        let value = ((rng.next_u64() as u128) << 64) | rng.next_u64() as u128;
+        if value >= threshold {
+            return value % bound;
+        }
+    }
+}